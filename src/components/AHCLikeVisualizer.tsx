@@ -1,6 +1,6 @@
 import type { FC } from 'react';
 import { useState, useEffect } from 'react';
-import { gen, get_max_turn as getMaxTurn, vis } from '../../public/wasm/rust';
+import { gen_with_params, Session } from '../../public/wasm/rust';
 import type { VisualizerSettingInfo, VisualizerResult } from '../types';
 import Description from './Description';
 import FileUploader from './FileUploader';
@@ -18,62 +18,105 @@ const AHCLikeVisualizer: FC = () => {
       turn: 0,
       maxTurn: 0,
       problemId: 'A',
+      allowOverLimit: false,
+      genParams: '',
+      trail: undefined,
+      heatmap: false,
     });
 
   const [visualizerResult, setVisualizerResult] = useState<VisualizerResult>({
     svgString: '',
     err: '',
     score: 0,
+    breakdown: '',
+    annotation: '',
+    warnings: '',
   });
 
+  // 生成パラメータが不正な場合は入力を更新しない
   useEffect(() => {
-    const inputText = gen(
-      visualizerSettingInfo.seed,
-      visualizerSettingInfo.problemId,
-    );
-    setVisualizerSettingInfo((prev) => ({ ...prev, input: inputText }));
-  }, [visualizerSettingInfo.seed, visualizerSettingInfo.problemId]);
+    try {
+      const inputText = gen_with_params(
+        visualizerSettingInfo.seed,
+        visualizerSettingInfo.problemId,
+        visualizerSettingInfo.genParams,
+      );
+      setVisualizerSettingInfo((prev) => ({ ...prev, input: inputText }));
+    } catch (e) {
+      console.log(e);
+    }
+  }, [
+    visualizerSettingInfo.seed,
+    visualizerSettingInfo.problemId,
+    visualizerSettingInfo.genParams,
+  ]);
+
+  // 入力・出力が変わったときだけパースとシミュレーションを行い、ターンの変更では使い回す
+  // 入力が不正な場合は、Session の作成時に行番号・列番号付きのエラーメッセージが返る
+  // Session は作成した effect のクリーンアップで解放する (StrictMode で effect が再実行されても解放済みの Session を使わない)
+  const [[session, sessionError], setSessionState] = useState<
+    [Session | null, string]
+  >([null, '']);
 
   useEffect(() => {
+    let created: Session | null = null;
     try {
-      const maxTurn = getMaxTurn(
+      created = new Session(
         visualizerSettingInfo.input,
         visualizerSettingInfo.output,
+        visualizerSettingInfo.problemId,
+        visualizerSettingInfo.allowOverLimit,
       );
-      setVisualizerSettingInfo((prev) => ({
-        ...prev,
-        maxTurn,
-        turn: 0,
-      }));
+      setSessionState([created, '']);
     } catch (e) {
-      // outputが不正な場合には計算ができない。そのときにはmaxTurnを0にする
-      setVisualizerSettingInfo((prev) => ({
-        ...prev,
-        maxTurn: 0,
-        turn: 0,
-      }));
+      console.log(e);
+      setSessionState([null, e instanceof Error ? e.message : String(e)]);
     }
+    return () => {
+      created?.free();
+    };
   }, [
-    visualizerSettingInfo.output,
     visualizerSettingInfo.input,
-    setVisualizerSettingInfo,
+    visualizerSettingInfo.output,
+    visualizerSettingInfo.problemId,
+    visualizerSettingInfo.allowOverLimit,
   ]);
 
   useEffect(() => {
+    // outputが不正な場合には計算ができない。そのときにはmaxTurnを0にする
+    const maxTurn = session?.max_turn() ?? 0;
+    setVisualizerSettingInfo((prev) => ({
+      ...prev,
+      maxTurn,
+      turn: 0,
+    }));
+  }, [session, setVisualizerSettingInfo]);
+
+  useEffect(() => {
+    if (session === null) {
+      setVisualizerResult({
+        svgString: 'invalid input or output',
+        err: sessionError,
+        score: 0,
+        breakdown: '',
+        annotation: '',
+        warnings: '',
+      });
+      return;
+    }
     try {
-      const ret = vis(
-        visualizerSettingInfo.input,
-        visualizerSettingInfo.output,
-        visualizerSettingInfo.turn,
-      );
-      console.log(ret);
+      session.set_trail(visualizerSettingInfo.trail);
+      session.set_heatmap(visualizerSettingInfo.heatmap);
       setVisualizerResult({
-        svgString: ret.svg,
-        err: ret.err,
-        score: Number(ret.score),
+        svgString: session.render(visualizerSettingInfo.turn),
+        err: session.error(),
+        score: Number(session.score()),
+        breakdown: session.breakdown(),
+        annotation: session.annotation(visualizerSettingInfo.turn),
+        warnings: session.warnings(),
       });
     } catch (e) {
-      // visが失敗した場合にはエラーを出力する
+      // 描画に失敗した場合にはエラーを出力する
       console.log(e);
       let msg = '';
       if (e instanceof Error) {
@@ -83,12 +126,17 @@ const AHCLikeVisualizer: FC = () => {
         svgString: 'invalid input or output',
         err: msg,
         score: 0,
+        breakdown: '',
+        annotation: '',
+        warnings: '',
       });
     }
   }, [
     visualizerSettingInfo.turn,
-    visualizerSettingInfo.input,
-    visualizerSettingInfo.output,
+    visualizerSettingInfo.trail,
+    visualizerSettingInfo.heatmap,
+    session,
+    sessionError,
   ]);
 
   return (
@@ -110,6 +158,9 @@ const AHCLikeVisualizer: FC = () => {
         svgString={visualizerResult.svgString}
         err={visualizerResult.err}
         score={visualizerResult.score}
+        breakdown={visualizerResult.breakdown}
+        annotation={visualizerResult.annotation}
+        warnings={visualizerResult.warnings}
       ></SvgViewer>
     </>
   );
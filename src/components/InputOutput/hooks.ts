@@ -1,10 +1,11 @@
 import JSZip from 'jszip';
-import { gen } from '../../../public/wasm/rust';
+import { gen_with_params } from '../../../public/wasm/rust';
 
 export const useDownloadInput = (): {
   downloadInput: (
     seed: number,
     problemNumber: string,
+    genParams: string,
     downloadCases: number,
     setButtonText: (content: string) => void,
   ) => void;
@@ -12,12 +13,13 @@ export const useDownloadInput = (): {
   const downloadInput = (
     seed: number,
     problemId: string,
+    genParams: string,
     downloadCases: number,
     setButtonText: (content: string) => void,
   ): void => {
     const zip = new JSZip();
     for (let i = 0; i < downloadCases; i++) {
-      const inputString = gen(seed + i, problemId);
+      const inputString = gen_with_params(seed + i, problemId, genParams);
       zip.file((seed + i).toString().padStart(4, '0') + '.txt', inputString);
     }
     /* eslint-disable*/
@@ -47,6 +47,41 @@ const InputOutput: FC<InputOutputProps> = ({
     }));
   };
 
+  const onChangeAllowOverLimit = (e: React.ChangeEvent<HTMLInputElement>) => {
+    setVisualizerSettingInfo((prev) => ({
+      ...prev,
+      allowOverLimit: e.target.checked,
+    }));
+  };
+
+  const onChangeTrailEnabled = (e: React.ChangeEvent<HTMLInputElement>) => {
+    setVisualizerSettingInfo((prev) => ({
+      ...prev,
+      trail: e.target.checked ? 20 : undefined,
+    }));
+  };
+
+  const onChangeTrailTurns = (e: React.ChangeEvent<HTMLInputElement>) => {
+    setVisualizerSettingInfo((prev) => ({
+      ...prev,
+      trail: Number(e.target.value),
+    }));
+  };
+
+  const onChangeHeatmap = (e: React.ChangeEvent<HTMLInputElement>) => {
+    setVisualizerSettingInfo((prev) => ({
+      ...prev,
+      heatmap: e.target.checked,
+    }));
+  };
+
+  const onChangeGenParams = (e: React.ChangeEvent<HTMLInputElement>) => {
+    setVisualizerSettingInfo((prev) => ({
+      ...prev,
+      genParams: e.target.value,
+    }));
+  };
+
   return (
     <>
       <div>
@@ -82,6 +117,7 @@ const InputOutput: FC<InputOutputProps> = ({
             downloadInput(
               visualizerSettingInfo.seed,
               visualizerSettingInfo.problemId,
+              visualizerSettingInfo.genParams,
               downloadCases,
               setButtonText,
             );
@@ -101,8 +137,63 @@ const InputOutput: FC<InputOutputProps> = ({
             <option value="A">A</option>
             <option value="B">B</option>
             <option value="C">C</option>
+            <option value="rail">rail</option>
           </select>
         </label>
+        <label
+          className={styles.leftMargin} //eslint-disable-line
+        >
+          <input
+            type="checkbox"
+            checked={visualizerSettingInfo.allowOverLimit}
+            onChange={onChangeAllowOverLimit}
+          />
+          操作数の上限を超えても再生する
+        </label>
+        <label
+          className={styles.leftMargin} //eslint-disable-line
+        >
+          <input
+            type="checkbox"
+            checked={visualizerSettingInfo.trail !== undefined}
+            onChange={onChangeTrailEnabled}
+          />
+          軌跡
+        </label>
+        {visualizerSettingInfo.trail !== undefined && (
+          <label>
+            (直近
+            <input
+              type="number"
+              value={visualizerSettingInfo.trail}
+              min="0"
+              onChange={onChangeTrailTurns}
+            />
+            ターン、0 なら全て)
+          </label>
+        )}
+        <label
+          className={styles.leftMargin} //eslint-disable-line
+        >
+          <input
+            type="checkbox"
+            checked={visualizerSettingInfo.heatmap}
+            onChange={onChangeHeatmap}
+          />
+          ヒートマップ
+        </label>
+      </div>
+      <div>
+        <label>
+          生成パラメータ:
+          <input
+            type="text"
+            value={visualizerSettingInfo.genParams}
+            placeholder="N=30 M=5 items=100 rock_density=0.3 noise_scale=5"
+            size={50}
+            onChange={onChangeGenParams}
+          />
+        </label>
       </div>
       <div>
         <label>
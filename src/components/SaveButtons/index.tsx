@@ -20,6 +20,10 @@ const SvgViewer: FC<SvgViewerProps> = ({ visualizerSettingInfo }) => {
       visualizerSettingInfo.input,
       visualizerSettingInfo.output,
       visualizerSettingInfo.turn,
+      visualizerSettingInfo.problemId,
+      visualizerSettingInfo.allowOverLimit,
+      visualizerSettingInfo.trail,
+      visualizerSettingInfo.heatmap,
     );
     const svg = new DOMParser()
       .parseFromString(ret.svg, 'image/svg+xml')
@@ -44,6 +48,10 @@ const SvgViewer: FC<SvgViewerProps> = ({ visualizerSettingInfo }) => {
     visualizerSettingInfo.input,
     visualizerSettingInfo.output,
     visualizerSettingInfo.turn,
+    visualizerSettingInfo.problemId,
+    visualizerSettingInfo.allowOverLimit,
+    visualizerSettingInfo.trail,
+    visualizerSettingInfo.heatmap,
   ]);
 
   const onSaveGif = useCallback(() => {
@@ -51,6 +59,10 @@ const SvgViewer: FC<SvgViewerProps> = ({ visualizerSettingInfo }) => {
     const input = visualizerSettingInfo.input;
     const output = visualizerSettingInfo.output;
     const maxTurn = visualizerSettingInfo.maxTurn;
+    const problemId = visualizerSettingInfo.problemId;
+    const allowOverLimit = visualizerSettingInfo.allowOverLimit;
+    const trail = visualizerSettingInfo.trail;
+    const heatmap = visualizerSettingInfo.heatmap;
     const step = 1;
     const delay = (step * 2000) / 60;
     const gif = new GIF({
@@ -78,7 +90,15 @@ const SvgViewer: FC<SvgViewerProps> = ({ visualizerSettingInfo }) => {
         String(Math.round((50.0 * t) / maxTurn)).padStart(3, ' ') +
           '% finished',
       );
-      const svgData = vis(input, output, t).svg;
+      const svgData = vis(
+        input,
+        output,
+        t,
+        problemId,
+        allowOverLimit,
+        trail,
+        heatmap,
+      ).svg;
       const svg = new DOMParser()
         .parseFromString(svgData, 'image/svg+xml')
         .getElementById('vis') as unknown as SVGSVGElement | null;
@@ -121,6 +141,10 @@ const SvgViewer: FC<SvgViewerProps> = ({ visualizerSettingInfo }) => {
     visualizerSettingInfo.input,
     visualizerSettingInfo.output,
     visualizerSettingInfo.maxTurn,
+    visualizerSettingInfo.problemId,
+    visualizerSettingInfo.allowOverLimit,
+    visualizerSettingInfo.trail,
+    visualizerSettingInfo.heatmap,
     setAnimationButtonDescription,
     setAnimationButtonDisabled,
   ]);
@@ -4,14 +4,39 @@ type SvgViewerProps = {
   svgString: string;
   err: string;
   score: number;
+  breakdown: string;
+  annotation: string;
+  warnings: string;
 };
 
-const SvgViewer: FC<SvgViewerProps> = ({ svgString, err, score }) => {
+const SvgViewer: FC<SvgViewerProps> = ({
+  svgString,
+  err,
+  score,
+  breakdown,
+  annotation,
+  warnings,
+}) => {
   return (
     <>
       <div>
         score={score} {err && <span style={{ color: 'red' }}>({err})</span>}
       </div>
+      {warnings && (
+        <details>
+          <summary style={{ color: 'darkorange' }}>
+            warnings ({warnings.split('\n').length})
+          </summary>
+          <pre>{warnings}</pre>
+        </details>
+      )}
+      {breakdown && (
+        <details>
+          <summary>score breakdown</summary>
+          <pre>{breakdown}</pre>
+        </details>
+      )}
+      {annotation && <pre>{annotation}</pre>}
       <div
         dangerouslySetInnerHTML={{
           __html: svgString,
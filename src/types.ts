@@ -5,10 +5,18 @@ export type VisualizerSettingInfo = {
   turn: number;
   maxTurn: number;
   problemId: string;
+  allowOverLimit: boolean;
+  genParams: string;
+  // プレイヤーの軌跡を描画するターン数 (0 なら全ターン、undefined なら描画しない)
+  trail: number | undefined;
+  heatmap: boolean;
 };
 
 export type VisualizerResult = {
   svgString: string;
   err: string;
   score: number;
+  breakdown: string;
+  annotation: string;
+  warnings: string;
 };
@@ -0,0 +1,214 @@
+use crate::problems::{DynProblem, Instance, Options};
+use crate::tools::json_str;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// 1ケース分の採点結果
+#[derive(Clone, Debug)]
+pub struct CaseResult {
+    pub name: String,
+    pub score: i64,
+    pub err: String,
+    /// err が出力の不正によるものである場合、その種類 (ProblemError::kind)
+    pub kind: Option<String>,
+    pub actions: usize,
+}
+
+impl CaseResult {
+    pub fn new(
+        problem: &dyn DynProblem,
+        opts: &Options,
+        name: &str,
+        input: &str,
+        output: &str,
+    ) -> CaseResult {
+        match problem.open(input, output, opts) {
+            Ok(instance) => CaseResult::from_instance(name, &*instance),
+            Err(err) => CaseResult::error(name, format!("Invalid input: {}", err)),
+        }
+    }
+
+    pub fn from_instance(name: &str, instance: &dyn Instance) -> CaseResult {
+        let err = instance.error();
+        CaseResult {
+            name: name.to_owned(),
+            score: instance.score(),
+            err: instance.error_message(),
+            kind: err.map(|e| e.kind().to_owned()),
+            actions: instance.max_turn(),
+        }
+    }
+
+    /// 採点まで進めなかったケース
+    pub fn error(name: &str, err: String) -> CaseResult {
+        CaseResult {
+            name: name.to_owned(),
+            score: 0,
+            err,
+            kind: None,
+            actions: 0,
+        }
+    }
+}
+
+/// in_dir 内の各 *.txt と、out_dir 内の同名ファイルを組にして並列に採点する
+pub fn score_dir(
+    problem: &dyn DynProblem,
+    opts: &Options,
+    in_dir: &Path,
+    out_dir: &Path,
+    threads: usize,
+) -> std::io::Result<Vec<CaseResult>> {
+    let mut files = vec![];
+    for entry in std::fs::read_dir(in_dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|e| e == "txt") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    let results = run_parallel(files.len(), threads, |i| {
+        let name = files[i].file_stem().unwrap().to_string_lossy().into_owned();
+        let out_file = out_dir.join(files[i].file_name().unwrap());
+        let input = match std::fs::read_to_string(&files[i]) {
+            Ok(s) => s,
+            Err(e) => return CaseResult::error(&name, format!("{}: {}", files[i].display(), e)),
+        };
+        let output = match std::fs::read_to_string(&out_file) {
+            Ok(s) => s,
+            Err(e) => return CaseResult::error(&name, format!("{}: {}", out_file.display(), e)),
+        };
+        CaseResult::new(problem, opts, &name, &input, &output)
+    });
+    Ok(results)
+}
+
+/// 0..n の各 i について f(i) を threads 本のスレッドで計算し、添字順に並べて返す
+pub fn run_parallel<T: Send, F: Fn(usize) -> T + Sync>(n: usize, threads: usize, f: F) -> Vec<T> {
+    let next = AtomicUsize::new(0);
+    let mut results = std::thread::scope(|s| {
+        let handles = (0..threads.max(1))
+            .map(|_| {
+                s.spawn(|| {
+                    let mut done = vec![];
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        if i >= n {
+                            break;
+                        }
+                        done.push((i, f(i)));
+                    }
+                    done
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+    results.sort_by_key(|r| r.0);
+    results.into_iter().map(|r| r.1).collect()
+}
+
+/// 全ケースのスコアの集計
+#[derive(Clone, Debug)]
+pub struct Summary {
+    pub count: usize,
+    pub errors: usize,
+    pub total: i64,
+    pub mean: f64,
+    pub min: i64,
+    pub max: i64,
+    /// 出力の不正によるエラーの種類ごとの件数
+    pub by_kind: Vec<(String, usize)>,
+}
+
+impl Summary {
+    pub fn new(results: &[CaseResult]) -> Summary {
+        let total = results.iter().map(|r| r.score).sum::<i64>();
+        Summary {
+            count: results.len(),
+            errors: results.iter().filter(|r| !r.err.is_empty()).count(),
+            total,
+            mean: if results.is_empty() {
+                0.0
+            } else {
+                total as f64 / results.len() as f64
+            },
+            min: results.iter().map(|r| r.score).min().unwrap_or(0),
+            max: results.iter().map(|r| r.score).max().unwrap_or(0),
+            by_kind: results
+                .iter()
+                .filter_map(|r| r.kind.clone())
+                .fold(BTreeMap::new(), |mut mp, k| {
+                    *mp.entry(k).or_insert(0) += 1;
+                    mp
+                })
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+impl std::fmt::Display for Summary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "cases = {} (errors = {})", self.count, self.errors)?;
+        for (kind, count) in &self.by_kind {
+            writeln!(f, "  {} = {}", kind, count)?;
+        }
+        writeln!(f, "total = {}", self.total)?;
+        writeln!(f, "mean = {:.2}", self.mean)?;
+        writeln!(f, "min = {}", self.min)?;
+        write!(f, "max = {}", self.max)
+    }
+}
+
+pub fn to_csv(results: &[CaseResult]) -> String {
+    let mut s = String::from("name,score,actions,kind,err\n");
+    for r in results {
+        s += &format!(
+            "{},{},{},{},\"{}\"\n",
+            r.name,
+            r.score,
+            r.actions,
+            r.kind.as_deref().unwrap_or(""),
+            r.err.replace('"', "\"\"")
+        );
+    }
+    s
+}
+
+pub fn to_json(results: &[CaseResult]) -> String {
+    let summary = Summary::new(results);
+    let cases = results
+        .iter()
+        .map(|r| {
+            format!(
+                "{{\"name\":{},\"score\":{},\"actions\":{},\"kind\":{},\"err\":{}}}",
+                json_str(&r.name),
+                r.score,
+                r.actions,
+                r.kind.as_deref().map_or("null".to_owned(), json_str),
+                json_str(&r.err)
+            )
+        })
+        .collect::<Vec<_>>();
+    let by_kind = summary
+        .by_kind
+        .iter()
+        .map(|(k, c)| format!("{}:{}", json_str(k), c))
+        .collect::<Vec<_>>();
+    format!(
+        "{{\"cases\":[{}],\"summary\":{{\"count\":{},\"errors\":{},\"total\":{},\"mean\":{},\"min\":{},\"max\":{},\"by_kind\":{{{}}}}}}}\n",
+        cases.join(","),
+        summary.count,
+        summary.errors,
+        summary.total,
+        summary.mean,
+        summary.min,
+        summary.max,
+        by_kind.join(",")
+    )
+}
@@ -0,0 +1,67 @@
+use clap::Parser;
+use rust::{batch, problems};
+use std::path::Path;
+
+#[derive(Parser, Debug)]
+struct Cli {
+    /// 入力ファイルのディレクトリ
+    #[clap(short = 'i', long = "in", default_value = "in")]
+    in_dir: String,
+    /// 出力ファイルのディレクトリ
+    #[clap(short = 'o', long = "out", default_value = "out")]
+    out_dir: String,
+    /// 問題番号
+    #[clap(short = 'p', long = "problem", default_value = "A")]
+    problem: String,
+    /// 並列数 (省略時は CPU 数)
+    #[clap(short = 'j', long = "jobs")]
+    jobs: Option<usize>,
+    /// CSV の書き出し先
+    #[clap(long = "csv")]
+    csv: Option<String>,
+    /// JSON の書き出し先
+    #[clap(long = "json")]
+    json: Option<String>,
+    /// 操作数の上限を超えた出力も最後まで再生し、上限までの部分のスコアを表示する
+    #[clap(long = "allow-over-limit")]
+    allow_over_limit: bool,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let problem = problems::get(&cli.problem).unwrap_or_else(|| {
+        eprintln!("unknown problem: {}", cli.problem);
+        std::process::exit(1)
+    });
+    let jobs = cli
+        .jobs
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()));
+    let opts = problems::Options {
+        allow_over_limit: cli.allow_over_limit,
+    };
+    let results = batch::score_dir(
+        problem,
+        &opts,
+        Path::new(&cli.in_dir),
+        Path::new(&cli.out_dir),
+        jobs,
+    )
+    .unwrap_or_else(|e| {
+        eprintln!("{}: {}", cli.in_dir, e);
+        std::process::exit(1)
+    });
+    for r in &results {
+        if r.err.is_empty() {
+            println!("{}\t{}\t{}", r.name, r.score, r.actions);
+        } else {
+            println!("{}\t{}\t{}\t{}", r.name, r.score, r.actions, r.err);
+        }
+    }
+    println!("{}", batch::Summary::new(&results));
+    if let Some(path) = &cli.csv {
+        std::fs::write(path, batch::to_csv(&results)).unwrap();
+    }
+    if let Some(path) = &cli.json {
+        std::fs::write(path, batch::to_json(&results)).unwrap();
+    }
+}
@@ -0,0 +1,53 @@
+use clap::Parser;
+use rust::problems;
+use rust::tools::GenParams;
+use std::io::prelude::*;
+
+#[derive(Parser, Debug)]
+struct Cli {
+    /// seed を1行に1つずつ書いたファイル
+    seeds: String,
+    /// 問題番号
+    #[clap(short = 'p', long = "problem", default_value = "A")]
+    problem: String,
+    /// 入力ファイルの出力先ディレクトリ
+    #[clap(short = 'd', long = "dir", default_value = "in")]
+    dir: String,
+    /// 生成パラメータ (例: `--params "N=30 M=5 items=100 rock_density=0.3 noise_scale=5"`)
+    ///
+    /// 指定できるのは N, M, items, rocks, rock_density, noise_scale (問題 A, B, C のみ)。
+    /// 省略した項目は従来と同じ既定値になる
+    #[clap(long = "params", default_value = "")]
+    params: GenParams,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let problem = problems::get(&cli.problem).unwrap_or_else(|| {
+        eprintln!("unknown problem: {}", cli.problem);
+        std::process::exit(1)
+    });
+    let seeds = std::fs::read_to_string(&cli.seeds).unwrap_or_else(|_| {
+        eprintln!("no such file: {}", cli.seeds);
+        std::process::exit(1)
+    });
+    std::fs::create_dir_all(&cli.dir).unwrap();
+    let mut id = 0;
+    for line in seeds.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let seed = line.parse::<u64>().unwrap_or_else(|_| {
+            eprintln!("invalid seed: {}", line);
+            std::process::exit(1)
+        });
+        let input = problem.generate(seed, &cli.params).unwrap_or_else(|e| {
+            eprintln!("failed to generate seed {}: {}", seed, e);
+            std::process::exit(1)
+        });
+        let mut f = std::fs::File::create(format!("{}/{:04}.txt", cli.dir, id)).unwrap();
+        write!(f, "{}", input).unwrap();
+        id += 1;
+    }
+}
@@ -0,0 +1,59 @@
+use clap::Parser;
+use rust::problems;
+
+#[derive(Parser, Debug)]
+struct Cli {
+    /// 入力ファイル
+    input: String,
+    /// 出力ファイル
+    output: String,
+    /// 問題番号
+    #[clap(short = 'p', long = "problem", default_value = "A")]
+    problem: String,
+    /// 各ターンの出来事を JSON で書き出すファイル
+    #[clap(long = "events")]
+    events: Option<String>,
+    /// 操作数の上限を超えた出力も最後まで再生し、上限までの部分のスコアを表示する
+    #[clap(long = "allow-over-limit")]
+    allow_over_limit: bool,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let problem = problems::get(&cli.problem).unwrap_or_else(|| {
+        eprintln!("unknown problem: {}", cli.problem);
+        std::process::exit(1)
+    });
+    let input = std::fs::read_to_string(&cli.input).unwrap_or_else(|_| {
+        eprintln!("no such file: {}", cli.input);
+        std::process::exit(1)
+    });
+    let output = std::fs::read_to_string(&cli.output).unwrap_or_else(|_| {
+        eprintln!("no such file: {}", cli.output);
+        std::process::exit(1)
+    });
+    let opts = problems::Options {
+        allow_over_limit: cli.allow_over_limit,
+    };
+    let instance = problem.open(&input, &output, &opts).unwrap_or_else(|e| {
+        eprintln!("invalid input: {}", e);
+        std::process::exit(1)
+    });
+    for warning in instance.warnings() {
+        eprintln!("warning: {}", warning);
+    }
+    println!("Score = {}", instance.score());
+    if let Some(err) = instance.error() {
+        println!("{}", err);
+    }
+    if let Some(err) = instance.replay_error() {
+        println!("after the limit: {}", err);
+    }
+    let (breakdown, _) = instance.breakdown();
+    if !breakdown.is_empty() {
+        println!("{}", breakdown);
+    }
+    if let Some(path) = &cli.events {
+        std::fs::write(path, instance.events()).unwrap();
+    }
+}
@@ -0,0 +1,291 @@
+use clap::Parser;
+use rust::{
+    batch,
+    problems::{self, DynProblem},
+    tools::GenParams,
+};
+use std::io::prelude::*;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+#[derive(Parser, Debug)]
+struct Cli {
+    /// 解答プログラムのコマンド (例: tester -- ./a.out)
+    #[clap(required = true, last = true)]
+    cmd: Vec<String>,
+    /// seed を1行に1つずつ書いたファイル (省略時は --start から --count 個)
+    #[clap(short = 's', long = "seeds")]
+    seeds: Option<String>,
+    #[clap(long = "start", default_value_t = 0)]
+    start: u64,
+    #[clap(short = 'n', long = "count", default_value_t = 100)]
+    count: u64,
+    /// 問題番号
+    #[clap(short = 'p', long = "problem", default_value = "A")]
+    problem: String,
+    /// 生成パラメータ (gen の --params と同じ形式)
+    #[clap(long = "params", default_value = "")]
+    params: GenParams,
+    /// 1ケースあたりの制限時間 (ミリ秒)
+    #[clap(short = 't', long = "time-limit", default_value_t = 2000)]
+    time_limit: u64,
+    /// 並列数
+    #[clap(short = 'j', long = "jobs", default_value_t = 1)]
+    jobs: usize,
+    /// 入力ファイルの保存先
+    #[clap(long = "in", default_value = "in")]
+    in_dir: String,
+    /// 出力ファイルの保存先
+    #[clap(long = "out", default_value = "out")]
+    out_dir: String,
+    /// CSV の書き出し先
+    #[clap(long = "csv")]
+    csv: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Verdict {
+    /// 正常終了し、出力も正しい
+    Ok,
+    /// 出力の形式が不正 (parse_output のエラー)
+    ParseError,
+    /// 出力の形式は正しいが、不正な操作を含む (compute_score のエラー)
+    WrongAnswer,
+    /// 制限時間超過
+    Tle,
+    /// 異常終了
+    Re,
+}
+
+impl std::fmt::Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Verdict::Ok => "OK",
+            Verdict::ParseError => "PE",
+            Verdict::WrongAnswer => "WA",
+            Verdict::Tle => "TLE",
+            Verdict::Re => "RE",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+struct TestResult {
+    seed: u64,
+    verdict: Verdict,
+    time: Duration,
+    case: batch::CaseResult,
+}
+
+/// 解答プログラムを起動する
+///
+/// unix では新しいプロセスグループで起動し、`sh -c ...` 経由などで生まれた子孫のプロセスも
+/// 制限時間超過時にまとめて kill できるようにする
+fn spawn(cmd: &[String]) -> std::io::Result<Child> {
+    let mut command = Command::new(&cmd[0]);
+    command
+        .args(&cmd[1..])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    command.spawn()
+}
+
+/// 解答プログラムとその子孫のプロセスを kill する
+#[cfg(unix)]
+fn kill(child: &mut Child) -> std::io::Result<()> {
+    // プロセスグループ ID は spawn で子プロセスの PID と同じにしてある
+    let status = Command::new("kill")
+        .args(["-KILL", "--", &format!("-{}", child.id())])
+        .status()?;
+    if !status.success() {
+        child.kill()?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn kill(child: &mut Child) -> std::io::Result<()> {
+    child.kill()
+}
+
+/// 解答プログラムを1回実行し、(標準出力, 終了ステータスのエラー, 経過時間) を返す
+/// 制限時間を超えた場合はプロセスグループごと kill して None を返す
+fn run(
+    cmd: &[String],
+    input: &str,
+    time_limit: Duration,
+) -> std::io::Result<Option<(String, String, Duration)>> {
+    let start = Instant::now();
+    let mut child = spawn(cmd)?;
+    let mut stdin = child.stdin.take().unwrap();
+    let input = input.to_owned();
+    let writer = std::thread::spawn(move || {
+        // 解答プログラムが入力を読み切らずに終了した場合のエラーは無視する
+        let _ = stdin.write_all(input.as_bytes());
+    });
+    let mut stdout = child.stdout.take().unwrap();
+    let reader = std::thread::spawn(move || {
+        let mut s = String::new();
+        let _ = stdout.read_to_string(&mut s);
+        s
+    });
+    let mut stderr = child.stderr.take().unwrap();
+    let err_reader = std::thread::spawn(move || {
+        let mut s = String::new();
+        let _ = stderr.read_to_string(&mut s);
+        s
+    });
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if start.elapsed() > time_limit {
+            kill(&mut child)?;
+            child.wait()?;
+            return Ok(None);
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    };
+    let time = start.elapsed();
+    writer.join().unwrap();
+    let output = reader.join().unwrap();
+    let stderr = err_reader.join().unwrap();
+    let err = if status.success() {
+        String::new()
+    } else {
+        let last = stderr.lines().last().unwrap_or("");
+        format!("{} {}", status, last).trim_end().to_owned()
+    };
+    Ok(Some((output, err, time)))
+}
+
+fn test(cli: &Cli, problem: &dyn DynProblem, seed: u64) -> TestResult {
+    let name = format!("{:04}", seed);
+    let input = problem.generate(seed, &cli.params).unwrap_or_else(|e| {
+        eprintln!("failed to generate seed {}: {}", seed, e);
+        std::process::exit(1)
+    });
+    std::fs::write(format!("{}/{}.txt", cli.in_dir, name), &input).unwrap();
+    let time_limit = Duration::from_millis(cli.time_limit);
+    let (verdict, time, case) = match run(&cli.cmd, &input, time_limit) {
+        Err(e) => (
+            Verdict::Re,
+            Duration::ZERO,
+            batch::CaseResult::error(&name, e.to_string()),
+        ),
+        Ok(None) => (
+            Verdict::Tle,
+            time_limit,
+            batch::CaseResult::error(&name, "TLE".to_owned()),
+        ),
+        Ok(Some((output, err, time))) => {
+            std::fs::write(format!("{}/{}.txt", cli.out_dir, name), &output).unwrap();
+            if !err.is_empty() {
+                (Verdict::Re, time, batch::CaseResult::error(&name, err))
+            } else {
+                // 入力は生成したものなので、パースに失敗することはない
+                let instance = problem
+                    .open(&input, &output, &problems::Options::default())
+                    .unwrap();
+                let case = batch::CaseResult::from_instance(&name, &*instance);
+                // parse_output で弾かれたもの (形式不正・操作数超過) とシミュレーション中のエラーを分ける
+                let verdict = if instance.is_parse_error() {
+                    Verdict::ParseError
+                } else if instance.error().is_some() {
+                    Verdict::WrongAnswer
+                } else {
+                    Verdict::Ok
+                };
+                (verdict, time, case)
+            }
+        }
+    };
+    TestResult {
+        seed,
+        verdict,
+        time,
+        case,
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let problem = problems::get(&cli.problem).unwrap_or_else(|| {
+        eprintln!("unknown problem: {}", cli.problem);
+        std::process::exit(1)
+    });
+    let seeds = match &cli.seeds {
+        Some(path) => {
+            let seeds = std::fs::read_to_string(path).unwrap_or_else(|_| {
+                eprintln!("no such file: {}", path);
+                std::process::exit(1)
+            });
+            seeds
+                .lines()
+                .map(|l| l.trim())
+                .filter(|l| !l.is_empty())
+                .map(|l| {
+                    l.parse::<u64>().unwrap_or_else(|_| {
+                        eprintln!("invalid seed: {}", l);
+                        std::process::exit(1)
+                    })
+                })
+                .collect::<Vec<_>>()
+        }
+        None => (cli.start..cli.start + cli.count).collect(),
+    };
+    std::fs::create_dir_all(&cli.in_dir).unwrap();
+    std::fs::create_dir_all(&cli.out_dir).unwrap();
+
+    let results = batch::run_parallel(seeds.len(), cli.jobs, |i| {
+        let r = test(&cli, problem, seeds[i]);
+        eprintln!(
+            "{}\t{}\t{}\t{}ms\t{}",
+            r.case.name,
+            r.verdict,
+            r.case.score,
+            r.time.as_millis(),
+            r.case.err
+        );
+        r
+    });
+
+    let cases = results.iter().map(|r| r.case.clone()).collect::<Vec<_>>();
+    println!("{}", batch::Summary::new(&cases));
+    for verdict in [
+        Verdict::Ok,
+        Verdict::WrongAnswer,
+        Verdict::ParseError,
+        Verdict::Tle,
+        Verdict::Re,
+    ] {
+        let count = results.iter().filter(|r| r.verdict == verdict).count();
+        if count > 0 {
+            println!("{} = {}", verdict, count);
+        }
+    }
+    let max_time = results.iter().map(|r| r.time).max().unwrap_or_default();
+    println!("max time = {}ms", max_time.as_millis());
+
+    if let Some(path) = &cli.csv {
+        let mut s = String::from("seed,verdict,score,actions,time_ms,err\n");
+        for r in &results {
+            s += &format!(
+                "{},{},{},{},{},\"{}\"\n",
+                r.seed,
+                r.verdict,
+                r.case.score,
+                r.case.actions,
+                r.time.as_millis(),
+                r.case.err.replace('"', "\"\"")
+            );
+        }
+        std::fs::write(path, s).unwrap();
+    }
+}
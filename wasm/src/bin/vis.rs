@@ -0,0 +1,80 @@
+use clap::Parser;
+use rust::problems;
+use std::path::Path;
+
+#[derive(Parser, Debug)]
+struct Cli {
+    /// 入力ファイル
+    input: String,
+    /// 出力ファイル
+    output: String,
+    /// 描画するターン (省略時は最終ターン)
+    #[clap(short = 't', long = "turn")]
+    turn: Option<usize>,
+    /// 問題番号
+    #[clap(short = 'p', long = "problem", default_value = "A")]
+    problem: String,
+    /// SVG の出力先ディレクトリ
+    #[clap(short = 'd', long = "dir", default_value = "vis")]
+    dir: String,
+    /// 操作数の上限を超えた出力も最後まで再生し、上限までの部分のスコアを表示する
+    #[clap(long = "allow-over-limit")]
+    allow_over_limit: bool,
+    /// プレイヤーの軌跡を直近何ターンぶん描画するか (0 なら全ターン)
+    #[clap(long = "trail")]
+    trail: Option<usize>,
+    /// 各マスにプレイヤーがいたターン数をヒートマップで描画する
+    #[clap(long = "heatmap")]
+    heatmap: bool,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let problem = problems::get(&cli.problem).unwrap_or_else(|| {
+        eprintln!("unknown problem: {}", cli.problem);
+        std::process::exit(1)
+    });
+    let input = std::fs::read_to_string(&cli.input).unwrap_or_else(|_| {
+        eprintln!("no such file: {}", cli.input);
+        std::process::exit(1)
+    });
+    let output = std::fs::read_to_string(&cli.output).unwrap_or_else(|_| {
+        eprintln!("no such file: {}", cli.output);
+        std::process::exit(1)
+    });
+    let opts = problems::Options {
+        allow_over_limit: cli.allow_over_limit,
+    };
+    let instance = problem.open(&input, &output, &opts).unwrap_or_else(|e| {
+        eprintln!("invalid input: {}", e);
+        std::process::exit(1)
+    });
+    for warning in instance.warnings() {
+        eprintln!("warning: {}", warning);
+    }
+    let max_turn = instance.max_turn();
+    let turn = cli.turn.unwrap_or(max_turn).min(max_turn);
+    std::fs::create_dir_all(&cli.dir).unwrap();
+    let name = Path::new(&cli.output)
+        .file_stem()
+        .unwrap()
+        .to_string_lossy();
+    let path = format!("{}/{}.svg", cli.dir, name);
+    let view = problems::View {
+        trail: cli.trail,
+        heatmap: cli.heatmap,
+    };
+    std::fs::write(&path, instance.render(turn, &view)).unwrap();
+    println!("Score = {}", instance.score());
+    if let Some(err) = instance.error() {
+        println!("{}", err);
+    }
+    if let Some(err) = instance.replay_error() {
+        println!("after the limit: {}", err);
+    }
+    eprintln!("turn {} / {} -> {}", turn, max_turn, path);
+    let annotation = instance.annotation(turn);
+    if !annotation.is_empty() {
+        eprintln!("{}", annotation);
+    }
+}
@@ -1,359 +1,219 @@
-use wasm_bindgen::prelude::*;
-mod tools;
-
-use svg::node::element::{Rectangle, Circle, Text, SVG};
-use svg::Document;
-use noise::{NoiseFn, Perlin};
-use proconio::{input, marker::Chars};
-use rand::prelude::*;
-use std::ops::RangeBounds;
+#![allow(non_snake_case)]
 
-pub trait SetMinMax {
-    fn setmin(&mut self, v: Self) -> bool;
-    fn setmax(&mut self, v: Self) -> bool;
-}
-impl<T> SetMinMax for T
-where
-    T: PartialOrd,
-{
-    fn setmin(&mut self, v: T) -> bool {
-        *self > v && {
-            *self = v;
-            true
-        }
-    }
-    fn setmax(&mut self, v: T) -> bool {
-        *self < v && {
-            *self = v;
-            true
-        }
-    }
-}
+use wasm_bindgen::prelude::*;
+pub mod batch;
+pub mod problems;
+pub mod tools;
 
-#[derive(Clone, Debug)]
-pub struct Input {
-    N: usize,
-    M: usize,
-    cs: Vec<Vec<char>>,
-}
+use std::cell::RefCell;
 
-impl std::fmt::Display for Input {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "{} {}", self.N, self.M)?;
-        for i in 0..self.N {
-            writeln!(f, "{}", self.cs[i].iter().collect::<String>())?;
-        }
-        Ok(())
-    }
+#[wasm_bindgen]
+pub fn gen(seed: i32, problemId: String) -> Result<String, String> {
+    let problem = get_problem(&problemId)?;
+    problem.generate(seed as u64, &tools::GenParams::default())
 }
 
-pub fn parse_input(f: &str) -> Input {
-    let f = proconio::source::once::OnceSource::from(f);
-    input! {
-        from f,
-        N: usize, M: usize,
-        cs: [Chars; N],
-    }
-    Input { N, M, cs }
+/// params (`N=30 items=80 noise_scale=5` のような `key=value` の列) で生成パラメータを変えて入力を生成する
+#[wasm_bindgen]
+pub fn gen_with_params(seed: i32, problemId: String, params: String) -> Result<String, String> {
+    let problem = get_problem(&problemId)?;
+    problem.generate(seed as u64, &params.parse()?)
 }
 
-pub fn read<T: Copy + PartialOrd + std::fmt::Display + std::str::FromStr, R: RangeBounds<T>>(
-    token: Option<&str>,
-    range: R,
-) -> Result<T, String> {
-    if let Some(v) = token {
-        if let Ok(v) = v.parse::<T>() {
-            if !range.contains(&v) {
-                Err(format!("Out of range: {}", v))
-            } else {
-                Ok(v)
-            }
-        } else {
-            Err(format!("Parse error: {}", v))
-        }
-    } else {
-        Err("Unexpected EOF".to_owned())
-    }
+fn get_problem(id: &str) -> Result<&'static dyn problems::DynProblem, String> {
+    problems::get(id).ok_or_else(|| format!("Unknown problem: {}", id))
 }
 
-#[derive(Clone, Debug, Copy)]
-pub enum Action {
-    Move(usize),
-    Carry(usize),
-    Roll(usize),
+#[wasm_bindgen(getter_with_clone)]
+pub struct Ret {
+    pub score: i64,
+    pub err: String,
+    pub svg: String,
+    /// スコアの内訳
+    pub breakdown: String,
+    /// 出力中の `#c` コメントによる、そのターンの注釈
+    pub annotation: String,
 }
 
-const DIJ: [(usize, usize); 4] = [(!0, 0), (1, 0), (0, !0), (0, 1)];
-const DIR: [char; 4] = ['U', 'D', 'L', 'R'];
-
-pub struct Output {
-    pub out: Vec<Action>,
+/// 入力と出力を一度だけパースし、採点結果やリプレイを保持したまま任意のターンを描画する
+#[wasm_bindgen]
+pub struct Session {
+    inner: Box<dyn problems::Instance>,
+    view: problems::View,
 }
 
-pub fn parse_output(_input: &Input, f: &str) -> Result<Output, String> {
-    let mut out = vec![];
-    let mut ss = f.split_whitespace().peekable();
-    while ss.peek().is_some() {
-        let a = read(ss.next(), 1..=3)?;
-        let dir = read(ss.next(), 'A'..='Z')?;
-        let Some(d) = DIR.iter().position(|&x| x == dir) else {
-            return Err(format!("Invalid direction: {}", dir));
+#[wasm_bindgen]
+impl Session {
+    /// allowOverLimit を true にすると、操作数の上限を超えた出力も最後まで再生する
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        input: String,
+        output: String,
+        problemId: String,
+        allowOverLimit: Option<bool>,
+    ) -> Result<Session, String> {
+        let problem = get_problem(&problemId)?;
+        let opts = problems::Options {
+            allow_over_limit: allowOverLimit.unwrap_or(false),
         };
-        out.push(match a {
-            1 => Action::Move(d),
-            2 => Action::Carry(d),
-            3 => Action::Roll(d),
-            _ => unreachable!(),
-        });
-    }
-    if out.len() > 10000 {
-        return Err("Too many actions".to_owned());
+        Ok(Session {
+            inner: problem.open(&input, &output, &opts)?,
+            view: problems::View::default(),
+        })
     }
-    Ok(Output { out })
-}
 
-pub fn compute_score(input: &Input, out: &Output) -> (i64, String) {
-    let (mut score, err, _) = compute_score_details(input, &out.out);
-    if err.len() > 0 {
-        score = 0;
+    pub fn max_turn(&self) -> usize {
+        self.inner.max_turn()
     }
-    (score, err)
-}
 
-pub fn compute_score_details(input: &Input, out: &[Action]) -> (i64, String, ()) {
-    let mut cs = input.cs.clone();
-    let mut pos = (0, 0);
-    let mut K = 0;
-    let mut A = 0;
-    for i in 0..input.N {
-        for j in 0..input.N {
-            if cs[i][j] == 'A' {
-                pos = (i, j);
-            } else if cs[i][j] >= 'a' && cs[i][j] <= 'z' {
-                K += 1;
-            }
-        }
+    /// turn ターン目の状態の SVG
+    pub fn render(&self, turn: usize) -> String {
+        self.inner.render(turn, &self.view)
     }
-    for t in 0..out.len() {
-        match out[t] {
-            Action::Move(d) => {
-                let (di, dj) = DIJ[d];
-                pos.0 += di;
-                pos.1 += dj;
-                if pos.0 >= input.N || pos.1 >= input.N {
-                    return (0, format!("Out of the board (turn {t})"), ());
-                }
-            }
-            Action::Carry(d) => {
-                let (di, dj) = DIJ[d];
-                if (cs[pos.0][pos.1] < 'a' || cs[pos.0][pos.1] > 'z') && cs[pos.0][pos.1] != '@' {
-                    return (0, format!("No item to carry (turn {t})"), ());
-                }
-                let c = cs[pos.0][pos.1];
-                cs[pos.0][pos.1] = '.';
-                pos.0 += di;
-                pos.1 += dj;
-                if pos.0 >= input.N || pos.1 >= input.N {
-                    return (0, format!("Out of the board (turn {t})"), ());
-                }
-                if matches!(cs[pos.0][pos.1], '@' | 'a'..='z') {
-                    return (0, format!("Collision (turn {t})"), ());
-                } else if matches!(cs[pos.0][pos.1], 'A'..='Z') {
-                    if cs[pos.0][pos.1].to_ascii_lowercase() == c {
-                        A += 1;
-                    }
-                } else {
-                    assert_eq!(cs[pos.0][pos.1], '.');
-                    cs[pos.0][pos.1] = c;
-                }
-            }
-            Action::Roll(d) => {
-                let (di, dj) = DIJ[d];
-                if (cs[pos.0][pos.1] < 'a' || cs[pos.0][pos.1] > 'z') && cs[pos.0][pos.1] != '@' {
-                    return (0, format!("No item to roll (turn {t})"), ());
-                }
-                let c = cs[pos.0][pos.1];
-                cs[pos.0][pos.1] = '.';
-                let mut crt = pos;
-                loop {
-                    let next = (crt.0 + di, crt.1 + dj);
-                    if next.0 >= input.N
-                        || next.1 >= input.N
-                        || matches!(cs[next.0][next.1], '@' | 'a'..='z')
-                    {
-                        cs[crt.0][crt.1] = c;
-                        break;
-                    } else if matches!(cs[next.0][next.1], 'A'..='Z') {
-                        if cs[next.0][next.1].to_ascii_lowercase() == c {
-                            A += 1;
-                        }
-                        break;
-                    } else {
-                        crt = next;
-                    }
-                }
-            }
-        }
-    }
-    let score = if A == K {
-        (1e6 * (1.0 + (1e4 / out.len() as f64).log2())).round() as i64
-    } else {
-        (1e6 * A as f64 / K as f64).round() as i64
-    };
-    (score, String::new(), ())
-}
 
-fn get_color(c: char) -> &'static str {
-    match c {
-        '@' => "#000000", // 黒
-        '.' => "#FFFFFF", // 白
-        'A' => "#FF0000",
-        'a' => "#FFFFFF",
-        'B' => "#00FF00",
-        'b' => "#FFFFFF",
-        'C' => "#0000FF",
-        'c' => "#FFFFFF",
-        _ => "#888888",   // デフォルト灰色
+    /// render でプレイヤーの軌跡を直近 turns ターンぶん描画する (0 なら全ターン、undefined なら描画しない)
+    pub fn set_trail(&mut self, turns: Option<usize>) {
+        self.view.trail = turns;
     }
-}
 
-/// 小文字のキャラに対応する丸の色を取得
-fn get_circle_color(c: char) -> &'static str {
-    match c {
-        'a' => "#FF8888", // 薄い赤
-        'b' => "#88FF88", // 薄い緑
-        'c' => "#8888FF", // 薄い青
-        _ => "#000000",   // 通常は黒
+    /// render で各マスにプレイヤーがいたターン数をヒートマップで描画するか
+    pub fn set_heatmap(&mut self, enabled: bool) {
+        self.view.heatmap = enabled;
     }
-}
-
-/// 20×20 の盤面を表す SVG を生成
-fn generate_svg(cs: Vec<Vec<char>>, x: usize, y: usize) -> String {
-    let cell_size = 21; // 各マスのサイズ
-    let circle_radius = 8;
-    let mut document = Document::new()
-        .set("viewBox", (0, 0, 441, 441)) // 余白を考慮して少し大きく
-        .set("width", "441px")
-        .set("height", "441px")
-        .set("preserveAspectRatio", "xMidYMid meet");
-
-    for (row_idx, row) in cs.iter().enumerate() {
-        for (col_idx, &c) in row.iter().enumerate() {
-            let x_pos = col_idx as i32 * cell_size;
-            let y_pos = row_idx as i32 * cell_size;
-
-            // マスの背景を描画
-            let rect = Rectangle::new()
-                .set("x", x_pos)
-                .set("y", y_pos)
-                .set("width", cell_size)
-                .set("height", cell_size)
-                .set("fill", get_color(c))
-                .set("stroke", "#000")
-                .set("stroke-width", 1);
 
-            document = document.add(rect);
-
-            // 小文字 (a, b, c) の場合、それぞれの色の小さな丸を描画
-            if let Some(circle_color) = match c {
-                'a' | 'b' | 'c' => Some(get_circle_color(c)),
-                _ => None,
-            } {
-                let circle = Circle::new()
-                    .set("cx", x_pos + cell_size / 2)
-                    .set("cy", y_pos + cell_size / 2)
-                    .set("r", circle_radius)
-                    .set("fill", circle_color);
-                document = document.add(circle);
-            }
-        }
+    pub fn score(&self) -> i64 {
+        self.inner.score()
     }
 
-    // 主人公の位置にキャラを描画 (例: 黄色い円)
-    let player_circle = Circle::new()
-        .set("cx", x as i32 * cell_size + cell_size / 2)
-        .set("cy", y as i32 * cell_size + cell_size / 2)
-        .set("r", 4)
-        .set("fill", "yellow")
-        .set("stroke", "black")
-        .set("stroke-width", 2);
+    /// 操作の数の上限を超えて再生した場合は、上限より後の不正な操作のエラーも含む
+    pub fn error(&self) -> String {
+        self.inner.error_message()
+    }
 
-    document = document.add(player_circle);
+    /// エラーの種類 (`Collision` など)。エラーがなければ空文字列
+    pub fn error_kind(&self) -> String {
+        self.inner
+            .error()
+            .map_or(String::new(), |e| e.kind().to_owned())
+    }
 
-    // 最下段に列番号 (0〜19) を追加
-    for col_idx in 0..cs[0].len() {
-        let x_pos = col_idx as i32 * cell_size + cell_size / 2;
-        let y_pos = cs.len() as i32 * cell_size + 15; // 下の余白
+    /// エラーが発生したターン
+    pub fn error_turn(&self) -> Option<usize> {
+        self.inner.error().and_then(|e| e.turn())
+    }
 
-        let text = Text::new("")
-            .set("x", x_pos)
-            .set("y", y_pos)
-            .set("text-anchor", "middle") // 中央揃え
-            .set("font-size", 12)
-            .set("fill", "black")
-            .add(svg::node::Text::new(format!("{}", col_idx)));
+    /// スコアの内訳 (運んだ荷物の数・色ごとの内訳・操作の種類ごとの回数など)
+    pub fn breakdown(&self) -> String {
+        self.inner.breakdown().0
+    }
 
-        document = document.add(text);
+    /// breakdown と同じ内容の JSON
+    pub fn breakdown_json(&self) -> String {
+        self.inner.breakdown().1
     }
 
-    // 最右列に行番号 (0〜19) を追加
-    for row_idx in 0..cs.len() {
-        let x_pos = cs[0].len() as i32 * cell_size + 5; // 右の余白
-        let y_pos = row_idx as i32 * cell_size + cell_size / 2 + 4;
+    /// 各ターンに起きた出来事 (荷物の運搬・転がし・穴への落下など) の JSON 配列
+    pub fn events(&self) -> String {
+        self.inner.events()
+    }
 
-        let text = Text::new("")
-            .set("x", x_pos)
-            .set("y", y_pos)
-            .set("text-anchor", "left") // 左揃え
-            .set("font-size", 12)
-            .set("fill", "black")
-            .add(svg::node::Text::new(format!("{}", row_idx)));
+    /// turn ターン目に表示する注釈 (直前の操作の前に出力された `#c` コメント)
+    pub fn annotation(&self, turn: usize) -> String {
+        self.inner.annotation(turn)
+    }
 
-        document = document.add(text);
+    /// 入力と出力についての警告 (同じ色の穴まで運べない荷物・塞がれた穴・読み飛ばした `#h` の行など)。1件1行
+    pub fn warnings(&self) -> String {
+        self.inner.warnings().join("\n")
     }
+}
 
-    document.to_string()
+/// 直前に vis / get_max_turn に渡された入出力とその Session
+///
+/// スライダーを動かしている間は入出力が変わらないので、パースとシミュレーションを使い回す
+struct Cache {
+    input: String,
+    output: String,
+    problem_id: String,
+    allow_over_limit: Option<bool>,
+    session: Session,
 }
 
-#[wasm_bindgen]
-pub fn gen(seed: i32, problemId: String) -> String {
-    let input = tools::generate(seed as u64, &problemId);
-    input.to_string()
+thread_local! {
+    static CACHE: RefCell<Option<Cache>> = const { RefCell::new(None) };
 }
 
-#[wasm_bindgen(getter_with_clone)]
-pub struct Ret {
-    pub score: i64,
-    pub err: String,
-    pub svg: String,
+fn with_session<T>(
+    input: String,
+    output: String,
+    problem_id: String,
+    allow_over_limit: Option<bool>,
+    f: impl FnOnce(&mut Session) -> T,
+) -> Result<T, String> {
+    CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        if !matches!(&*cache, Some(c) if c.input == input && c.output == output && c.problem_id == problem_id && c.allow_over_limit == allow_over_limit)
+        {
+            *cache = Some(Cache {
+                session: Session::new(
+                    input.clone(),
+                    output.clone(),
+                    problem_id.clone(),
+                    allow_over_limit,
+                )?,
+                input,
+                output,
+                problem_id,
+                allow_over_limit,
+            });
+        }
+        Ok(f(&mut cache.as_mut().unwrap().session))
+    })
 }
 
 #[wasm_bindgen]
-pub fn vis(input: String, output: String, turn: usize) -> Ret {
-    let input = tools::parse_input(&input);
-    let output = tools::parse_output(&input, &output);
-    let (score, err, svg) = match output {
-        Ok(out) => {
-            let actions = &out.out[..turn];
-            let (score, err) = tools::compute_score(&input, &out);
-            let (cs, (x, y)) = tools::get_grid(&input, &actions);
-            (score, err, generate_svg(cs, y, x))
-        }
-        Err(err) => {
-            let (cs, (x, y)) = tools::get_grid(&input, &[]);
-            (0, err, generate_svg(cs, y, x))
+pub fn vis(
+    input: String,
+    output: String,
+    turn: usize,
+    problemId: String,
+    allowOverLimit: Option<bool>,
+    trail: Option<usize>,
+    heatmap: Option<bool>,
+) -> Result<Ret, String> {
+    with_session(input, output, problemId, allowOverLimit, |session| {
+        session.set_trail(trail);
+        session.set_heatmap(heatmap.unwrap_or(false));
+        Ret {
+            score: session.score(),
+            err: session.error(),
+            svg: session.render(turn),
+            breakdown: session.breakdown(),
+            annotation: session.annotation(turn),
         }
-    };
+    })
+}
 
-    Ret { score, err, svg }
+#[wasm_bindgen]
+pub fn events(
+    input: String,
+    output: String,
+    problemId: String,
+    allowOverLimit: Option<bool>,
+) -> Result<String, String> {
+    with_session(input, output, problemId, allowOverLimit, |session| {
+        session.events()
+    })
 }
 
 #[wasm_bindgen]
-pub fn get_max_turn(input: String, output: String) -> usize {
-    let input = tools::parse_input(&input);
-    let output = tools::parse_output(&input, &output);
-    match output {
-        Ok(out) => out.out.len(),
-        Err(_) => 0,
-    }
+pub fn get_max_turn(
+    input: String,
+    output: String,
+    problemId: String,
+    allowOverLimit: Option<bool>,
+) -> Result<usize, String> {
+    with_session(input, output, problemId, allowOverLimit, |session| {
+        session.max_turn()
+    })
 }
@@ -0,0 +1,106 @@
+use super::{Options, Problem, View};
+use crate::tools::{self, Breakdown, GenParams, Input, Replay, ScoreError, Sim};
+
+pub mod vis;
+
+pub use vis::{generate_svg, Layout, Overlay, Status, CANVAS_SIZE};
+
+/// 荷物を穴に運ぶ問題 (問題番号 A, B, C は入力の生成方法だけが異なる)
+#[derive(Clone, Copy, Debug)]
+pub struct Carry(pub &'static str);
+
+impl Problem for Carry {
+    type Input = Input;
+    type Output = Replay;
+    type Error = ScoreError;
+
+    fn generate(&self, seed: u64, params: &GenParams) -> Result<Input, String> {
+        tools::generate_with(seed, self.0, params)
+    }
+
+    fn parse_input(&self, f: &str) -> Result<Input, String> {
+        tools::parse_input(f).map_err(|e| e.to_string())
+    }
+
+    fn parse_output(&self, input: &Input, f: &str, opts: &Options) -> Result<Replay, ScoreError> {
+        let out = if opts.allow_over_limit {
+            tools::parse_output_unlimited(input, f)
+        } else {
+            tools::parse_output(input, f)
+        };
+        out.map(|out| Replay::new(input, out))
+    }
+
+    fn score(&self, _input: &Input, out: &Replay) -> (i64, Option<ScoreError>) {
+        (out.score, out.err.clone())
+    }
+
+    fn replay_error(&self, _input: &Input, out: &Replay) -> Option<ScoreError> {
+        out.replay_err.clone()
+    }
+
+    fn max_turn(&self, _input: &Input, out: &Replay) -> usize {
+        out.max_turn()
+    }
+
+    /// 不正な操作より後のターンでは、その直前の盤面を原因のマスを強調して描画する
+    /// 出力に `#h` の注釈があれば、そのマスも強調する
+    fn render(&self, input: &Input, out: Option<&Replay>, turn: usize, view: &View) -> String {
+        let Some(replay) = out else {
+            let sim = Sim::new(input);
+            let overlay = Overlay {
+                status: Some(Status::new(&sim, 0, None)),
+                ..Overlay::default()
+            };
+            return generate_svg(sim.cs, sim.pos.1, sim.pos.0, input.M, &overlay);
+        };
+        let sim = replay.state(turn);
+        let steps = replay.steps(turn);
+        let visits = view.heatmap.then(|| replay.visits(turn));
+        let overlay = Overlay {
+            err: replay.error_at(turn),
+            highlights: replay
+                .annotation(turn)
+                .map_or(&[][..], |a| &a.highlights[..]),
+            trail: match view.trail {
+                Some(0) => steps,
+                Some(k) => &steps[steps.len().saturating_sub(k)..],
+                None => &[],
+            },
+            last: replay.last_step(turn),
+            visits: visits.as_deref(),
+            deliveries: Some(&sim.deliveries),
+            status: Some(Status::new(
+                &sim,
+                replay.max_turn(),
+                replay.last_step(turn).map(|s| s.action),
+            )),
+        };
+        generate_svg(sim.cs, sim.pos.1, sim.pos.0, input.M, &overlay)
+    }
+
+    fn annotation(&self, _input: &Input, out: &Replay, turn: usize) -> String {
+        out.annotation(turn).map_or(String::new(), |a| a.text())
+    }
+
+    fn warnings(&self, input: &Input, out: Option<&Replay>) -> Vec<String> {
+        let mut ret = tools::solvability::analyze(input).warnings();
+        if let Some(out) = out {
+            ret.extend(out.warnings.iter().cloned());
+        }
+        ret
+    }
+
+    fn breakdown(&self, input: &Input, out: Option<&Replay>) -> (String, String) {
+        let breakdown = match out {
+            Some(replay) => replay.breakdown.clone(),
+            None => Breakdown::new(input),
+        };
+        (breakdown.to_string(), breakdown.to_json())
+    }
+
+    fn events(&self, input: &Input, out: &Replay) -> String {
+        let (events, _) = tools::compute_events(input, &out.actions);
+        tools::event::events_to_json(&events)
+    }
+}
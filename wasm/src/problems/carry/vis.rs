@@ -0,0 +1,640 @@
+//! 荷物を運ぶ問題の盤面の描画
+
+use crate::problems::draw::create_rectangle;
+use crate::tools::{Action, ScoreError, Sim, StepResult};
+use std::collections::BTreeMap;
+use svg::node::element::{Circle, Definitions, Line, Marker, Path, Rectangle, Text};
+use svg::Document;
+
+/// 盤面全体 (番号・凡例の余白を含む) の長辺の長さ
+pub const CANVAS_SIZE: f64 = 600.0;
+
+/// 盤面の大きさとキャンバスの大きさから決まる描画位置
+///
+/// マスの大きさは、右に行番号、下に列番号と凡例の余白を取ったうえで盤面がキャンバスに収まるように決める
+#[derive(Clone, Copy, Debug)]
+pub struct Layout {
+    pub rows: usize,
+    pub cols: usize,
+    /// 1マスの一辺の長さ
+    pub cell: f64,
+    /// 行番号・列番号・凡例の文字の大きさ
+    pub font: f64,
+    /// 行番号・列番号を何行・何列おきに描くか (マスが文字より小さいと番号同士が重なるため間引く)
+    pub label_step: usize,
+    /// 凡例の1行あたりの色の数
+    pub legend_per_line: usize,
+    /// 盤面の右に置く状態表示の左端の x 座標
+    pub panel_x: f64,
+    /// SVG 全体の幅と高さ
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Layout {
+    /// colors 色ぶんの凡例を含めた配置を計算する
+    pub fn new(rows: usize, cols: usize, colors: usize, canvas: f64) -> Layout {
+        let rows = rows.max(1);
+        let cols = cols.max(1);
+        let font = (canvas / rows.max(cols) as f64 * 0.55).clamp(6.0, 12.0);
+        // 行番号は最大の番号の桁数ぶん、列番号は1文字ぶんの余白を取る
+        let digits = (rows - 1).to_string().len() as f64;
+        let margin_right = font * 0.6 * digits + font * 0.5;
+        let legend_per_line = ((canvas / (font * LEGEND_WIDTH)) as usize).max(1);
+        let legend_lines = colors.div_ceil(legend_per_line);
+        let margin_bottom = font * 1.5 + legend_lines as f64 * font * LEGEND_HEIGHT;
+        let cell =
+            ((canvas - margin_right) / cols as f64).min((canvas - margin_bottom) / rows as f64);
+        // 番号1つぶんの幅と高さに収まるよう、1, 2, 5, 10, 20, ... おきに間引く
+        let label_size = (font * 0.6 * digits + font * 0.4).max(font * 1.1);
+        let label_step = [1, 2, 5]
+            .iter()
+            .flat_map(|&k| (0..4).map(move |e| k * 10usize.pow(e)))
+            .filter(|&k| k as f64 * cell >= label_size)
+            .min()
+            .unwrap_or(10000);
+        let panel_x = (cell * cols as f64 + margin_right)
+            .max(colors.min(legend_per_line) as f64 * font * LEGEND_WIDTH)
+            + font;
+        Layout {
+            rows,
+            cols,
+            cell,
+            font,
+            label_step,
+            legend_per_line,
+            panel_x,
+            width: panel_x + font * PANEL_WIDTH,
+            height: (cell * rows as f64 + margin_bottom).max(font * PANEL_LINE * 6.0),
+        }
+    }
+
+    /// (i, j) のマスの左上の座標
+    pub fn corner(&self, (i, j): (usize, usize)) -> (f64, f64) {
+        (j as f64 * self.cell, i as f64 * self.cell)
+    }
+
+    /// (i, j) のマスの中心の座標
+    pub fn center(&self, (i, j): (usize, usize)) -> (f64, f64) {
+        ((j as f64 + 0.5) * self.cell, (i as f64 + 0.5) * self.cell)
+    }
+
+    /// マスの上に荷物や穴の文字を描くか
+    pub fn letters(&self) -> bool {
+        self.cell >= MIN_LETTER_CELL
+    }
+
+    /// 元の 21px のマスを基準に、線の太さや丸の半径を拡大縮小する
+    pub fn scale(&self, v: f64) -> f64 {
+        v * self.cell / 21.0
+    }
+
+    /// 凡例の k 番目の色の左上の座標
+    pub fn legend(&self, k: usize) -> (f64, f64) {
+        let top = self.rows as f64 * self.cell + self.font * 1.5;
+        (
+            (k % self.legend_per_line) as f64 * self.font * LEGEND_WIDTH,
+            top + (k / self.legend_per_line) as f64 * self.font * LEGEND_HEIGHT,
+        )
+    }
+}
+
+/// マスの上に文字を描く最小のマスの大きさ (これより小さいと文字が読めないので色だけで表す)
+const MIN_LETTER_CELL: f64 = 8.0;
+
+/// 直前の操作で動いた荷物の矢印の色
+const ARROW_COLOR: &str = "#D81B60";
+
+/// 状態表示の幅と1行の高さ (文字の大きさに対する比)
+const PANEL_WIDTH: f64 = 14.0;
+const PANEL_LINE: f64 = 1.6;
+
+/// 凡例の1色あたりの幅と高さ (文字の大きさに対する比)
+const LEGEND_WIDTH: f64 = 4.0;
+const LEGEND_HEIGHT: f64 = 1.8;
+
+/// OKLCH 色空間の色を sRGB に変換する (色域外なら None)
+fn oklch_to_rgb(l: f64, c: f64, h: f64) -> Option<[f64; 3]> {
+    let (a, b) = (c * h.to_radians().cos(), c * h.to_radians().sin());
+    let l_ = (l + 0.3963377774 * a + 0.2158037573 * b).powi(3);
+    let m_ = (l - 0.1055613458 * a - 0.0638541728 * b).powi(3);
+    let s_ = (l - 0.0894841775 * a - 1.2914855480 * b).powi(3);
+    let rgb = [
+        4.0767416621 * l_ - 3.3077115913 * m_ + 0.2309699292 * s_,
+        -1.2684380046 * l_ + 2.6097574011 * m_ - 0.3413193965 * s_,
+        -0.0041960863 * l_ - 0.7034186147 * m_ + 1.7076147010 * s_,
+    ];
+    if rgb.iter().any(|&v| !(-1e-9..=1.0 + 1e-9).contains(&v)) {
+        return None;
+    }
+    Some(rgb.map(|v| {
+        let v = v.clamp(0.0, 1.0);
+        if v <= 0.0031308 {
+            12.92 * v
+        } else {
+            1.055 * v.powf(1.0 / 2.4) - 0.055
+        }
+    }))
+}
+
+fn to_hex(rgb: [f64; 3]) -> String {
+    format!(
+        "#{:02X}{:02X}{:02X}",
+        (rgb[0] * 255.0).round() as u8,
+        (rgb[1] * 255.0).round() as u8,
+        (rgb[2] * 255.0).round() as u8
+    )
+}
+
+/// 穴と荷物の色 (m 色)
+///
+/// OKLCH 色空間で色相を等間隔に取り、色数が多い場合は隣り合う色の明度を交互に変えて見分けやすくする
+#[derive(Clone, Debug)]
+pub struct Palette {
+    /// k 番目の色の (穴の色, 荷物の丸の色, 穴の上の文字の色)
+    colors: Vec<(String, String, &'static str)>,
+}
+
+impl Palette {
+    pub fn new(m: usize) -> Palette {
+        let mut colors = vec![];
+        for k in 0..m {
+            let h = 30.0 + 360.0 * k as f64 / m as f64;
+            let l = if m <= 8 || k % 2 == 0 { 0.65 } else { 0.45 };
+            // 色域に収まるまで彩度を下げる
+            let mut c = 0.2;
+            let rgb = loop {
+                if let Some(rgb) = oklch_to_rgb(l, c, h) {
+                    break rgb;
+                }
+                c -= 0.005;
+            };
+            let light = rgb.map(|v| v + (1.0 - v) * 0.5);
+            let luminance = 0.2126 * rgb[0] + 0.7152 * rgb[1] + 0.0722 * rgb[2];
+            let text = if luminance > 0.5 { "black" } else { "white" };
+            colors.push((to_hex(rgb), to_hex(light), text));
+        }
+        Palette { colors }
+    }
+
+    fn index(c: char) -> Option<usize> {
+        c.is_ascii_alphabetic()
+            .then(|| (c.to_ascii_lowercase() as u8 - b'a') as usize)
+    }
+
+    /// マスの背景色
+    fn cell(&self, c: char) -> &str {
+        match c {
+            '@' => "#000000", // 黒
+            '.' => "#FFFFFF", // 白
+            'A'..='Z' => self.get(c).map_or("#888888", |p| p.0.as_str()),
+            'a'..='z' => "#FFFFFF",
+            _ => "#888888", // デフォルト灰色
+        }
+    }
+
+    /// 荷物の丸の色
+    fn circle(&self, c: char) -> &str {
+        self.get(c).map_or("#888888", |p| p.1.as_str())
+    }
+
+    /// 穴の上に書く文字の色
+    fn text(&self, c: char) -> &'static str {
+        self.get(c).map_or("black", |p| p.2)
+    }
+
+    fn get(&self, c: char) -> Option<&(String, String, &'static str)> {
+        Palette::index(c).and_then(|k| self.colors.get(k))
+    }
+}
+
+/// 盤面の上に重ねて描画するもの
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Overlay<'a> {
+    /// エラーの原因となったマスを赤枠で強調する
+    pub err: Option<&'a ScoreError>,
+    /// 出力の `#h` コメントで指定された (マス, 色) で、そのマスを半透明に塗る
+    pub highlights: &'a [((usize, usize), String)],
+    /// プレイヤーの軌跡として描画する操作 (古い順)
+    pub trail: &'a [StepResult],
+    /// 直前に実行した操作 (運搬・転がしなら荷物の動きを矢印で描画し、運搬中の荷物をプレイヤーの上に描画する)
+    pub last: Option<&'a StepResult>,
+    /// 各マスにプレイヤーがいたターン数 (与えられればヒートマップとして描画する)
+    pub visits: Option<&'a [Vec<usize>]>,
+    /// 盤面の右に表示する状態
+    pub status: Option<Status>,
+    /// 穴のマスごとの、ここまでに運んだ荷物の数 (ツールチップに表示する)
+    pub deliveries: Option<&'a BTreeMap<(usize, usize), usize>>,
+}
+
+/// 盤面の右に表示する、そのターンまでの進み具合
+#[derive(Clone, Copy, Debug)]
+pub struct Status {
+    pub turn: usize,
+    pub max_turn: usize,
+    /// 直前に実行した操作
+    pub last: Option<Action>,
+    /// 正しい穴に運んだ荷物の数
+    pub delivered: usize,
+    /// 荷物の総数
+    pub K: usize,
+    /// ここまでの操作列に対するスコア
+    pub score: i64,
+}
+
+impl Status {
+    /// sim の盤面まで進めたときの状態 (不正な操作より後のターンでは、その直前のターンになる)
+    pub fn new(sim: &Sim, max_turn: usize, last: Option<Action>) -> Status {
+        Status {
+            turn: sim.turn,
+            max_turn,
+            last,
+            delivered: sim.A,
+            K: sim.K,
+            score: sim.score(),
+        }
+    }
+}
+
+/// 盤面を表す SVG を生成
+/// m は色の数で、盤面に M 色より多くの文字があればそれも含めて色を割り当てる
+pub fn generate_svg(cs: Vec<Vec<char>>, x: usize, y: usize, m: usize, overlay: &Overlay) -> String {
+    let rows = cs.len();
+    let cols = cs.first().map_or(0, |row| row.len());
+    let m = cs
+        .iter()
+        .flatten()
+        .filter_map(|&c| Palette::index(c))
+        .map(|k| k + 1)
+        .fold(m, usize::max);
+    let palette = Palette::new(m);
+    let layout = Layout::new(rows, cols, m, CANVAS_SIZE);
+    let cell_size = layout.cell;
+    let stroke_width = layout.scale(1.0).min(1.0);
+    let max_visits = overlay
+        .visits
+        .map_or(0, |v| v.iter().flatten().copied().max().unwrap_or(0));
+    let mut document = Document::new()
+        .set("id", "vis")
+        .set("viewBox", (0.0, 0.0, layout.width, layout.height))
+        .set("width", format!("{}px", layout.width))
+        .set("height", format!("{}px", layout.height))
+        .set("preserveAspectRatio", "xMidYMid meet");
+
+    // 荷物の動きを表す矢印の先端
+    let arrow_head = Marker::new()
+        .set("id", "arrow")
+        .set("viewBox", (0, 0, 10, 10))
+        .set("refX", 8)
+        .set("refY", 5)
+        .set("markerWidth", 4)
+        .set("markerHeight", 4)
+        .set("orient", "auto")
+        .add(
+            Path::new()
+                .set("d", "M 0 0 L 10 5 L 0 10 z")
+                .set("fill", ARROW_COLOR),
+        );
+    document = document.add(Definitions::new().add(arrow_head));
+
+    for (row_idx, row) in cs.iter().enumerate() {
+        for (col_idx, &c) in row.iter().enumerate() {
+            let (x_pos, y_pos) = layout.corner((row_idx, col_idx));
+
+            // マスの背景を描画
+            let rect = Rectangle::new()
+                .set("x", x_pos)
+                .set("y", y_pos)
+                .set("width", cell_size)
+                .set("height", cell_size)
+                .set("fill", palette.cell(c))
+                .set("stroke", "#000")
+                .set("stroke-width", stroke_width);
+
+            document = document.add(rect);
+
+            // いたターン数が多いマスほど濃い赤で塗る
+            if let Some(visits) = overlay.visits {
+                let v = visits[row_idx][col_idx];
+                if v > 0 {
+                    let rect = Rectangle::new()
+                        .set("x", x_pos)
+                        .set("y", y_pos)
+                        .set("width", cell_size)
+                        .set("height", cell_size)
+                        .set("fill", "#E53935")
+                        .set("fill-opacity", 0.1 + 0.7 * v as f64 / max_visits as f64)
+                        .set("stroke", "none");
+                    document = document.add(rect);
+                }
+            }
+
+            // 小文字の場合は色の付いた丸、大文字の場合は穴の上に文字を描画
+            let (cx, cy) = layout.center((row_idx, col_idx));
+            let text_color = if c.is_ascii_lowercase() {
+                let circle = Circle::new()
+                    .set("cx", cx)
+                    .set("cy", cy)
+                    .set("r", layout.scale(8.0))
+                    .set("fill", palette.circle(c));
+                document = document.add(circle);
+                "black"
+            } else if c.is_ascii_uppercase() {
+                palette.text(c)
+            } else {
+                continue;
+            };
+            if layout.letters() {
+                document = document.add(letter(c, cx, cy, cell_size * 0.5, text_color));
+            }
+        }
+    }
+
+    // 注釈で指定されたマスを強調
+    for (p, color) in overlay.highlights {
+        let (x_pos, y_pos) = layout.corner(*p);
+        let rect = Rectangle::new()
+            .set("x", x_pos)
+            .set("y", y_pos)
+            .set("width", cell_size)
+            .set("height", cell_size)
+            .set("fill", color.as_str())
+            .set("fill-opacity", 0.5)
+            .set("stroke", color.as_str())
+            .set("stroke-width", layout.scale(2.0));
+        document = document.add(rect);
+    }
+
+    // プレイヤーの軌跡を、移動は青の細線・運搬は緑の太線・転がしは荷物の動きを橙の破線で描画し、古いものほど薄くする
+    for (age, step) in overlay.trail.iter().rev().enumerate() {
+        let opacity = 1.0 - 0.8 * age as f64 / overlay.trail.len() as f64;
+        let (from, to, color, width, dashed) = match step.action {
+            Action::Move(_) => (step.from, step.to, "#1E88E5", 1.5, false),
+            Action::Carry(_) => (step.from, step.to, "#2E7D32", 3.0, false),
+            Action::Roll(_) => (step.from, step.item_to, "#EF6C00", 2.0, true),
+        };
+        let dash = if dashed {
+            format!("{} {}", layout.scale(4.0), layout.scale(3.0))
+        } else {
+            "none".to_owned()
+        };
+        let (x1, y1) = layout.center(from);
+        let (x2, y2) = layout.center(to);
+        let line = Line::new()
+            .set("x1", x1)
+            .set("y1", y1)
+            .set("x2", x2)
+            .set("y2", y2)
+            .set("stroke", color)
+            .set("stroke-width", layout.scale(width))
+            .set("stroke-linecap", "round")
+            .set("stroke-dasharray", dash)
+            .set("stroke-opacity", opacity);
+        document = document.add(line);
+    }
+
+    // 直前の操作で動いた荷物を、元のマスから止まったマス (または落ちた穴) への矢印で描画
+    if let Some(step) = overlay
+        .last
+        .filter(|s| s.item.is_some() && s.from != s.item_to)
+    {
+        let (x1, y1) = layout.center(step.from);
+        let (x2, y2) = layout.center(step.item_to);
+        let line = Line::new()
+            .set("x1", x1)
+            .set("y1", y1)
+            .set("x2", x2)
+            .set("y2", y2)
+            .set("stroke", ARROW_COLOR)
+            .set("stroke-width", layout.scale(2.5))
+            .set("marker-end", "url(#arrow)");
+        document = document.add(line);
+    }
+
+    // 主人公の位置にキャラを描画 (例: 黄色い円)
+    let (cx, cy) = layout.center((y, x));
+    let player_circle = Circle::new()
+        .set("cx", cx)
+        .set("cy", cy)
+        .set("r", layout.scale(4.0))
+        .set("fill", "yellow")
+        .set("stroke", "black")
+        .set("stroke-width", layout.scale(2.0));
+
+    document = document.add(player_circle);
+
+    // 運んでいる荷物をプレイヤーの右上に重ねて描画 (穴に落ちた場合は描画しない)
+    if let Some(step) = overlay.last {
+        if let (Action::Carry(_), Some(c), None) = (step.action, step.item, step.hole) {
+            let (cx, cy) = (cx + cell_size * 0.25, cy - cell_size * 0.25);
+            let fill = if c == '@' {
+                palette.cell(c)
+            } else {
+                palette.circle(c)
+            };
+            let circle = Circle::new()
+                .set("cx", cx)
+                .set("cy", cy)
+                .set("r", layout.scale(5.0))
+                .set("fill", fill)
+                .set("stroke", "black")
+                .set("stroke-width", stroke_width);
+            document = document.add(circle);
+            if c != '@' && layout.letters() {
+                document = document.add(letter(c, cx, cy, cell_size * 0.35, "black"));
+            }
+        }
+    }
+
+    // 不正な操作のプレイヤー位置と移動先を赤枠で強調
+    if let Some(err) = overlay.err {
+        for p in err.pos.into_iter().chain(err.target) {
+            let (x_pos, y_pos) = layout.corner(p);
+            let rect = Rectangle::new()
+                .set("x", x_pos)
+                .set("y", y_pos)
+                .set("width", cell_size)
+                .set("height", cell_size)
+                .set("fill", "none")
+                .set("stroke", "red")
+                .set("stroke-width", layout.scale(3.0));
+            document = document.add(rect);
+        }
+    }
+
+    // 最下段に列番号を追加
+    for col_idx in (0..cols).step_by(layout.label_step) {
+        let x_pos = layout.center((0, col_idx)).0;
+        let y_pos = rows as f64 * cell_size + layout.font * 1.1; // 下の余白
+
+        let text = Text::new("")
+            .set("x", x_pos)
+            .set("y", y_pos)
+            .set("text-anchor", "middle") // 中央揃え
+            .set("font-size", layout.font)
+            .set("fill", "black")
+            .add(svg::node::Text::new(format!("{}", col_idx)));
+
+        document = document.add(text);
+    }
+
+    // 最右列に行番号を追加
+    for row_idx in (0..rows).step_by(layout.label_step) {
+        let x_pos = cols as f64 * cell_size + layout.font * 0.3; // 右の余白
+        let y_pos = layout.center((row_idx, 0)).1 + layout.font / 3.0;
+
+        let text = Text::new("")
+            .set("x", x_pos)
+            .set("y", y_pos)
+            .set("text-anchor", "start") // 左揃え
+            .set("font-size", layout.font)
+            .set("fill", "black")
+            .add(svg::node::Text::new(format!("{}", row_idx)));
+
+        document = document.add(text);
+    }
+
+    // 盤面の右にターン・直前の操作・運んだ荷物の数・ここまでのスコアを表示
+    if let Some(status) = overlay.status {
+        let mut lines = vec![
+            (
+                format!("turn {} / {}", status.turn, status.max_turn),
+                "black",
+            ),
+            match status.last {
+                Some(a) => (format!("last: {} ({})", a, a.name()), "black"),
+                None => ("last: -".to_owned(), "black"),
+            },
+            (
+                format!("delivered: {} / {}", status.delivered, status.K),
+                "black",
+            ),
+            (format!("score: {}", status.score), "black"),
+        ];
+        if let Some(err) = overlay.err {
+            lines.push((format!("error: {}", err.kind.name()), "red"));
+        }
+        let rect = Rectangle::new()
+            .set("x", layout.panel_x)
+            .set("y", 0)
+            .set("width", layout.font * (PANEL_WIDTH - 1.0))
+            .set(
+                "height",
+                layout.font * PANEL_LINE * (lines.len() as f64 + 0.5),
+            )
+            .set("fill", "#F5F5F5")
+            .set("stroke", "#000")
+            .set("stroke-width", stroke_width);
+        document = document.add(rect);
+        for (k, (line, color)) in lines.into_iter().enumerate() {
+            let text = Text::new("")
+                .set("x", layout.panel_x + layout.font * 0.5)
+                .set("y", layout.font * PANEL_LINE * (k as f64 + 1.0))
+                .set("font-size", layout.font)
+                .set("fill", color)
+                .add(svg::node::Text::new(line));
+            document = document.add(text);
+        }
+    }
+
+    // 各マスにマウスを乗せると、位置・マスの文字・色・穴に運んだ荷物の数を表示する
+    for (row_idx, row) in cs.iter().enumerate() {
+        for (col_idx, &c) in row.iter().enumerate() {
+            let mut statement = vec![
+                format!("({}, {})", row_idx, col_idx),
+                format!("cell: {}", c),
+            ];
+            match c {
+                'a'..='z' => statement.push(format!("item color: {} ({})", c, palette.circle(c))),
+                'A'..='Z' => {
+                    statement.push(format!("hole color: {} ({})", c, palette.cell(c)));
+                    let delivered = overlay
+                        .deliveries
+                        .and_then(|d| d.get(&(row_idx, col_idx)))
+                        .map_or(0, |&k| k);
+                    statement.push(format!("delivered: {}", delivered));
+                }
+                '@' => statement.push("rock".to_owned()),
+                _ => {}
+            }
+            if (row_idx, col_idx) == (y, x) {
+                statement.push("player".to_owned());
+            }
+            let (x_pos, y_pos) = layout.corner((row_idx, col_idx));
+            let rect = create_rectangle(
+                x_pos,
+                y_pos,
+                cell_size,
+                cell_size,
+                "white",
+                0.0,
+                None,
+                0.0,
+                Some(statement.join("\n")),
+            );
+            document = document.add(rect);
+        }
+    }
+
+    // 列番号の下に、各色の穴と荷物の凡例を追加
+    let size = layout.font * 1.4;
+    for k in 0..m {
+        let hole = (b'A' + k as u8) as char;
+        let item = hole.to_ascii_lowercase();
+        let (x_pos, y_pos) = layout.legend(k);
+        let rect = Rectangle::new()
+            .set("x", x_pos)
+            .set("y", y_pos)
+            .set("width", size)
+            .set("height", size)
+            .set("fill", palette.cell(hole))
+            .set("stroke", "#000")
+            .set("stroke-width", stroke_width);
+        document = document.add(rect);
+        let (cx, cy) = (x_pos + size * 0.5, y_pos + size * 0.5);
+        document = document.add(letter(hole, cx, cy, layout.font, palette.text(hole)));
+        let circle = Circle::new()
+            .set("cx", cx + size * 1.3)
+            .set("cy", cy)
+            .set("r", size * 0.4)
+            .set("fill", palette.circle(item));
+        document = document.add(circle);
+        document = document.add(letter(item, cx + size * 1.3, cy, layout.font, "black"));
+    }
+
+    document.to_string()
+}
+
+/// (cx, cy) を中心に1文字を描画する
+fn letter(c: char, cx: f64, cy: f64, size: f64, color: &str) -> Text {
+    Text::new("")
+        .set("x", cx)
+        .set("y", cy)
+        .set("text-anchor", "middle")
+        .set("dominant-baseline", "central")
+        .set("font-size", size)
+        .set("fill", color)
+        .add(svg::node::Text::new(c.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_board_draws_every_label_and_letter() {
+        let layout = Layout::new(20, 20, 3, CANVAS_SIZE);
+        assert_eq!(layout.label_step, 1);
+        assert!(layout.letters());
+    }
+
+    #[test]
+    fn large_board_thins_labels_and_skips_letters() {
+        let layout = Layout::new(1000, 1000, 26, CANVAS_SIZE);
+        assert!(layout.cell < layout.font);
+        assert!(layout.label_step as f64 * layout.cell >= layout.font);
+        assert!(!layout.letters());
+        let layout = Layout::new(100, 100, 3, CANVAS_SIZE);
+        assert_eq!(layout.label_step, 2);
+    }
+}
@@ -0,0 +1,34 @@
+//! 複数の問題の描画で共通に使う部品
+
+use svg::node::element::{Rectangle, Title};
+
+/// 長方形を作る (title を与えると、マウスを乗せたときにツールチップとして表示される)
+#[allow(clippy::too_many_arguments)]
+pub fn create_rectangle(
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    fill: &str,
+    fill_opacity: f64,
+    stroke: Option<&str>,
+    stroke_width: f64,
+    title: Option<String>,
+) -> Rectangle {
+    let mut rect = Rectangle::new()
+        .set("x", x)
+        .set("y", y)
+        .set("width", width)
+        .set("height", height)
+        .set("fill", fill)
+        .set("fill-opacity", fill_opacity);
+    if let Some(stroke) = stroke {
+        rect = rect.set("stroke", stroke).set("stroke-width", stroke_width);
+    }
+
+    if let Some(title) = title {
+        rect = rect.add(Title::new(title));
+    }
+
+    rect
+}
@@ -0,0 +1,247 @@
+//! コンテストごとの処理をまとめる `Problem` トレイトと、問題番号からそれを引く登録表
+//!
+//! 新しいコンテストに対応するときは、このディレクトリにモジュールを1つ追加して
+//! `Problem` を実装し、`PROBLEMS` に登録する
+
+use crate::tools::{GenParams, ScoreError};
+
+pub mod carry;
+pub mod draw;
+pub mod rail;
+
+/// 出力の読み込み方の設定
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Options {
+    /// 操作の数が上限を超える出力も拒否せずに最後まで再生する
+    /// (スコアは 0 のままで、上限までの部分のスコアを内訳に表示する)
+    pub allow_over_limit: bool,
+}
+
+/// 描画の設定 (パースやシミュレーションをやり直さずに切り替えられるもの)
+#[derive(Clone, Copy, Debug, Default)]
+pub struct View {
+    /// プレイヤーの軌跡を直近何ターンぶん描画するか (Some(0) なら全ターン、None なら描画しない)
+    pub trail: Option<usize>,
+    /// 各マスにプレイヤーがいたターン数をヒートマップで描画する
+    pub heatmap: bool,
+}
+
+/// 1つのコンテストの入力生成・パース・採点・描画
+pub trait Problem: Clone + Sync + 'static {
+    type Input: std::fmt::Display;
+    type Output;
+    type Error: ProblemError + Clone;
+
+    /// params で指定されていない項目は問題ごとの既定値を使う
+    fn generate(&self, seed: u64, params: &GenParams) -> Result<Self::Input, String>;
+    /// 入力の形式や制約に誤りがあれば、その位置を含むメッセージを返す
+    fn parse_input(&self, f: &str) -> Result<Self::Input, String>;
+    fn parse_output(
+        &self,
+        input: &Self::Input,
+        f: &str,
+        opts: &Options,
+    ) -> Result<Self::Output, Self::Error>;
+    /// スコアと、出力が不正だった場合のエラー
+    fn score(&self, input: &Self::Input, out: &Self::Output) -> (i64, Option<Self::Error>);
+    /// 操作の数の上限を超えて再生した場合に、上限より後で見つかった不正な操作のエラー
+    fn replay_error(&self, _input: &Self::Input, _out: &Self::Output) -> Option<Self::Error> {
+        None
+    }
+    fn max_turn(&self, input: &Self::Input, out: &Self::Output) -> usize;
+    /// turn ターン目の状態の SVG (出力が不正な場合 out は None)
+    fn render(
+        &self,
+        input: &Self::Input,
+        out: Option<&Self::Output>,
+        turn: usize,
+        view: &View,
+    ) -> String;
+
+    /// スコアの内訳の (テキスト, JSON)
+    fn breakdown(&self, _input: &Self::Input, _out: Option<&Self::Output>) -> (String, String) {
+        (String::new(), "{}".to_owned())
+    }
+    /// 各ターンに起きた出来事の JSON 配列
+    fn events(&self, _input: &Self::Input, _out: &Self::Output) -> String {
+        "[]".to_owned()
+    }
+    /// turn ターン目に表示する、出力中のコメントによる注釈
+    fn annotation(&self, _input: &Self::Input, _out: &Self::Output, _turn: usize) -> String {
+        String::new()
+    }
+    /// 入力が解けない・解きにくい可能性や、出力中の読み飛ばした行があれば、その内容 (1件1行)
+    ///
+    /// スコアには影響しない。open で一度だけ呼ばれる (出力のパースに失敗した場合 out は None)
+    fn warnings(&self, _input: &Self::Input, _out: Option<&Self::Output>) -> Vec<String> {
+        vec![]
+    }
+}
+
+/// 問題ごとのエラー型が共通に持つ情報
+pub trait ProblemError: std::fmt::Display {
+    /// エラーの種類 (分類できない場合は空文字列)
+    fn kind(&self) -> &str;
+    /// エラーが発生したターン
+    fn turn(&self) -> Option<usize>;
+}
+
+impl ProblemError for String {
+    fn kind(&self) -> &str {
+        ""
+    }
+    fn turn(&self) -> Option<usize> {
+        None
+    }
+}
+
+impl ProblemError for ScoreError {
+    fn kind(&self) -> &str {
+        self.kind.name()
+    }
+    fn turn(&self) -> Option<usize> {
+        Some(self.turn)
+    }
+}
+
+/// 入力と出力をパースして採点まで済ませた状態
+pub trait Instance {
+    fn max_turn(&self) -> usize;
+    fn render(&self, turn: usize, view: &View) -> String;
+    fn score(&self) -> i64;
+    fn error(&self) -> Option<&dyn ProblemError>;
+    /// 操作の数の上限より後で見つかった不正な操作のエラー
+    fn replay_error(&self) -> Option<&dyn ProblemError>;
+    /// error と replay_error をまとめたメッセージ (エラーがなければ空文字列)
+    fn error_message(&self) -> String {
+        let mut msg = self.error().map(|e| e.to_string()).unwrap_or_default();
+        if let Some(e) = self.replay_error() {
+            msg += &format!("; after the limit: {}", e);
+        }
+        msg
+    }
+    /// 出力のパースに失敗したか
+    fn is_parse_error(&self) -> bool;
+    fn breakdown(&self) -> (String, String);
+    fn events(&self) -> String;
+    fn annotation(&self, turn: usize) -> String;
+    fn warnings(&self) -> &[String];
+}
+
+/// 問題番号だけで扱えるようにした Problem
+pub trait DynProblem: Sync {
+    fn generate(&self, seed: u64, params: &GenParams) -> Result<String, String>;
+    /// 入力が不正な場合はエラーを返す (出力が不正な場合は Instance の error に入る)
+    fn open(&self, input: &str, output: &str, opts: &Options) -> Result<Box<dyn Instance>, String>;
+}
+
+impl<P: Problem> DynProblem for P {
+    fn generate(&self, seed: u64, params: &GenParams) -> Result<String, String> {
+        Problem::generate(self, seed, params).map(|input| input.to_string())
+    }
+
+    fn open(&self, input: &str, output: &str, opts: &Options) -> Result<Box<dyn Instance>, String> {
+        let input = self.parse_input(input)?;
+        let output = self.parse_output(&input, output, opts);
+        let (score, err) = match &output {
+            Ok(out) => self.score(&input, out),
+            Err(err) => (0, Some(err.clone())),
+        };
+        let replay_err = output
+            .as_ref()
+            .ok()
+            .and_then(|out| self.replay_error(&input, out));
+        let warnings = self.warnings(&input, output.as_ref().ok());
+        Ok(Box::new(Loaded {
+            problem: self.clone(),
+            input,
+            output,
+            score,
+            err,
+            replay_err,
+            warnings,
+        }))
+    }
+}
+
+struct Loaded<P: Problem> {
+    problem: P,
+    input: P::Input,
+    output: Result<P::Output, P::Error>,
+    score: i64,
+    err: Option<P::Error>,
+    replay_err: Option<P::Error>,
+    /// 読み込み時に一度だけ計算した警告
+    warnings: Vec<String>,
+}
+
+impl<P: Problem> Instance for Loaded<P> {
+    fn max_turn(&self) -> usize {
+        match &self.output {
+            Ok(out) => self.problem.max_turn(&self.input, out),
+            Err(_) => 0,
+        }
+    }
+
+    fn render(&self, turn: usize, view: &View) -> String {
+        self.problem
+            .render(&self.input, self.output.as_ref().ok(), turn, view)
+    }
+
+    fn score(&self) -> i64 {
+        self.score
+    }
+
+    fn error(&self) -> Option<&dyn ProblemError> {
+        self.err.as_ref().map(|e| e as &dyn ProblemError)
+    }
+
+    fn replay_error(&self) -> Option<&dyn ProblemError> {
+        self.replay_err.as_ref().map(|e| e as &dyn ProblemError)
+    }
+
+    fn is_parse_error(&self) -> bool {
+        self.output.is_err()
+    }
+
+    fn breakdown(&self) -> (String, String) {
+        self.problem
+            .breakdown(&self.input, self.output.as_ref().ok())
+    }
+
+    fn events(&self) -> String {
+        match &self.output {
+            Ok(out) => self.problem.events(&self.input, out),
+            Err(_) => "[]".to_owned(),
+        }
+    }
+
+    fn annotation(&self, turn: usize) -> String {
+        match &self.output {
+            Ok(out) => self.problem.annotation(&self.input, out, turn),
+            Err(_) => String::new(),
+        }
+    }
+
+    fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+}
+
+/// 登録されている問題 (問題番号, 処理)
+static PROBLEMS: &[(&str, &dyn DynProblem)] = &[
+    ("A", &carry::Carry("A")),
+    ("B", &carry::Carry("B")),
+    ("C", &carry::Carry("C")),
+    ("rail", &rail::Rail),
+];
+
+/// 問題番号に対応する処理
+pub fn get(id: &str) -> Option<&'static dyn DynProblem> {
+    PROBLEMS.iter().find(|p| p.0 == id).map(|p| p.1)
+}
+
+/// 登録されている問題番号の一覧
+pub fn ids() -> Vec<&'static str> {
+    PROBLEMS.iter().map(|p| p.0).collect()
+}
@@ -0,0 +1,508 @@
+//! 駅と線路を建設して通勤者から運賃を得る問題
+//!
+//! N×N の盤面に M 人の通勤者の家 (src) と職場 (dst) があり、資金 K から始めて T ターンの間、
+//! 毎ターン駅 (0) か線路 (1〜6) を1つ建設するか何もしない (-1)。
+//! 家と職場の両方からマンハッタン距離 2 以内に、線路でつながった駅がある通勤者からは、
+//! 毎ターン家と職場のマンハッタン距離だけの運賃が得られる。最終的な資金がスコアとなる。
+
+use super::{Options, Problem, ProblemError, View};
+use crate::tools::{read, read_token, tokens, GenParams, InputError};
+use rand::prelude::*;
+
+mod vis;
+
+pub const COST_STATION: i64 = 5000;
+pub const COST_RAIL: i64 = 100;
+
+/// 駅・線路の種類ごとの接続方向 (上, 下, 左, 右)
+const OPEN: [[bool; 4]; 7] = [
+    [true, true, true, true],   // 0: 駅
+    [false, false, true, true], // 1: 左右
+    [true, true, false, false], // 2: 上下
+    [false, true, true, false], // 3: 左下
+    [true, false, true, false], // 4: 左上
+    [true, false, false, true], // 5: 右上
+    [false, true, false, true], // 6: 右下
+];
+/// 入力の上限 (コンテストの制約)
+pub const MAX_N: usize = 50;
+pub const MAX_M: usize = 1600;
+pub const MAX_K: i64 = 20000;
+pub const MAX_T: usize = 800;
+
+const DIJ: [(usize, usize); 4] = [(!0, 0), (1, 0), (0, !0), (0, 1)];
+
+#[derive(Clone, Debug)]
+pub struct Input {
+    pub n: usize,
+    pub m: usize,
+    pub k: i64,
+    pub t: usize,
+    pub src: Vec<(usize, usize)>,
+    pub dst: Vec<(usize, usize)>,
+}
+
+impl std::fmt::Display for Input {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} {} {} {}", self.n, self.m, self.k, self.t)?;
+        for i in 0..self.m {
+            writeln!(
+                f,
+                "{} {} {} {}",
+                self.src[i].0, self.src[i].1, self.dst[i].0, self.dst[i].1
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// 入力をパースし、形式と制約を検査する
+///
+/// - 1行目は N M K T で、それぞれ MAX_N, MAX_M, MAX_K, MAX_T 以下
+/// - 続く M 行はそれぞれ家と職場の座標で、盤面の内側にあり、家と職場のマンハッタン距離は 5 以上
+pub fn parse_input(f: &str) -> Result<Input, InputError> {
+    let lines = f.lines().collect::<Vec<_>>();
+    let line = |i: usize| tokens(lines.get(i).copied().unwrap_or(""));
+    let head = line(0);
+    let n = read_token(&head, 0, 1, 1..=MAX_N)?;
+    let m = read_token(&head, 1, 1, 1..=MAX_M)?;
+    let k = read_token(&head, 2, 1, 0..=MAX_K)?;
+    let t = read_token(&head, 3, 1, 1..=MAX_T)?;
+    let mut src = vec![];
+    let mut dst = vec![];
+    for i in 0..m {
+        let ts = line(i + 1);
+        let mut v = [0; 4];
+        for (j, v) in v.iter_mut().enumerate() {
+            *v = read_token(&ts, j, i + 2, 0..n)?;
+        }
+        let (s, d) = ((v[0], v[1]), (v[2], v[3]));
+        if dist(s, d) < 5 {
+            return Err(InputError::new(
+                i + 2,
+                None,
+                "Distance between home and workplace is less than 5",
+            ));
+        }
+        src.push(s);
+        dst.push(d);
+    }
+    Ok(Input {
+        n,
+        m,
+        k,
+        t,
+        src,
+        dst,
+    })
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Action {
+    Wait,
+    /// (種類, 行, 列)
+    Build(usize, usize, usize),
+}
+
+#[derive(Clone, Debug)]
+pub struct Output {
+    pub out: Vec<Action>,
+}
+
+/// エラーの種類と発生したターン
+#[derive(Clone, Debug)]
+pub struct RailError {
+    pub kind: &'static str,
+    pub turn: usize,
+    pub msg: String,
+}
+
+impl std::fmt::Display for RailError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (turn {})", self.msg, self.turn)
+    }
+}
+
+impl ProblemError for RailError {
+    fn kind(&self) -> &str {
+        self.kind
+    }
+    fn turn(&self) -> Option<usize> {
+        Some(self.turn)
+    }
+}
+
+pub fn parse_output(input: &Input, f: &str) -> Result<Output, RailError> {
+    let mut out = vec![];
+    let mut ss = f.split_whitespace().peekable();
+    while ss.peek().is_some() {
+        let turn = out.len();
+        let err = |msg| RailError {
+            kind: "ParseError",
+            turn,
+            msg,
+        };
+        let p = read(ss.next(), -1..=6).map_err(err)?;
+        if p == -1 {
+            out.push(Action::Wait);
+            continue;
+        }
+        let r = read(ss.next(), 0..input.n).map_err(err)?;
+        let c = read(ss.next(), 0..input.n).map_err(err)?;
+        out.push(Action::Build(p as usize, r, c));
+    }
+    if out.len() > input.t {
+        return Err(RailError {
+            kind: "TooManyActions",
+            turn: input.t,
+            msg: "Too many actions".to_owned(),
+        });
+    }
+    Ok(Output { out })
+}
+
+pub fn gen(seed: u64) -> Input {
+    let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(seed);
+    let n = MAX_N;
+    let t = MAX_T;
+    let m = rng.gen_range(50..=MAX_M);
+    let k = rng.gen_range(11000..=MAX_K);
+    // M は最大で盤面のマス数を超えるので、複数の家や職場が同じマスにあってもよい
+    let mut src = vec![];
+    let mut dst = vec![];
+    while src.len() < m {
+        let s = (rng.gen_range(0..n), rng.gen_range(0..n));
+        let d = (rng.gen_range(0..n), rng.gen_range(0..n));
+        if dist(s, d) < 5 {
+            continue;
+        }
+        src.push(s);
+        dst.push(d);
+    }
+    Input {
+        n,
+        m,
+        k,
+        t,
+        src,
+        dst,
+    }
+}
+
+fn dist(a: (usize, usize), b: (usize, usize)) -> usize {
+    a.0.abs_diff(b.0) + a.1.abs_diff(b.1)
+}
+
+/// 盤面の状態
+#[derive(Clone, Debug)]
+pub struct State {
+    /// 各マスの駅・線路の種類 (何もなければ None)
+    pub grid_state: Vec<Vec<Option<usize>>>,
+    pub money: i64,
+    /// 1ターンあたりの収入
+    pub income: i64,
+    pub turn: usize,
+}
+
+pub fn kind_v_to_usize(v: &Option<usize>) -> usize {
+    v.unwrap_or(usize::MAX)
+}
+
+/// 描画に使う情報
+pub struct VisData {
+    pub state: State,
+}
+
+struct UnionFind {
+    par: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(n: usize) -> UnionFind {
+        UnionFind {
+            par: (0..n).collect(),
+        }
+    }
+    fn find(&mut self, x: usize) -> usize {
+        if self.par[x] != x {
+            let r = self.find(self.par[x]);
+            self.par[x] = r;
+        }
+        self.par[x]
+    }
+    fn unite(&mut self, a: usize, b: usize) {
+        let a = self.find(a);
+        let b = self.find(b);
+        self.par[a] = b;
+    }
+}
+
+/// 盤面を1ターンずつ進めるシミュレータ
+pub struct Sim<'a> {
+    input: &'a Input,
+    pub state: State,
+    uf: UnionFind,
+}
+
+impl<'a> Sim<'a> {
+    pub fn new(input: &'a Input) -> Sim<'a> {
+        Sim {
+            input,
+            state: State {
+                grid_state: vec![vec![None; input.n]; input.n],
+                money: input.k,
+                income: 0,
+                turn: 0,
+            },
+            uf: UnionFind::new(input.n * input.n),
+        }
+    }
+
+    /// 1ターン分の建設と収入の受け取りを行う
+    ///
+    /// 建設できない場合は状態を変更せずにエラーを返す
+    pub fn step(&mut self, action: Action) -> Result<(), RailError> {
+        let turn = self.state.turn;
+        if let Action::Build(p, r, c) = action {
+            let err = |kind, msg: &str| RailError {
+                kind,
+                turn,
+                msg: msg.to_owned(),
+            };
+            let cost = if p == 0 { COST_STATION } else { COST_RAIL };
+            match self.state.grid_state[r][c] {
+                Some(0) => return Err(err("InvalidPlacement", "Station already exists")),
+                Some(_) if p != 0 => return Err(err("InvalidPlacement", "Rail already exists")),
+                _ => {}
+            }
+            if self.state.money < cost {
+                return Err(err("InsufficientFunds", "Not enough money"));
+            }
+            self.state.money -= cost;
+            self.state.grid_state[r][c] = Some(p);
+            self.connect(r, c);
+            self.state.income = self.compute_income();
+        }
+        self.state.money += self.state.income;
+        self.state.turn += 1;
+        Ok(())
+    }
+
+    fn connect(&mut self, r: usize, c: usize) {
+        let n = self.input.n;
+        let Some(p) = self.state.grid_state[r][c] else {
+            return;
+        };
+        for d in 0..4 {
+            let (nr, nc) = (r.wrapping_add(DIJ[d].0), c.wrapping_add(DIJ[d].1));
+            if nr >= n || nc >= n || !OPEN[p][d] {
+                continue;
+            }
+            if let Some(q) = self.state.grid_state[nr][nc] {
+                if OPEN[q][d ^ 1] {
+                    self.uf.unite(r * n + c, nr * n + nc);
+                }
+            }
+        }
+    }
+
+    /// 家と職場の近くの駅が同じ連結成分に属する通勤者からの収入の合計
+    fn compute_income(&mut self) -> i64 {
+        let mut income = 0;
+        for i in 0..self.input.m {
+            let a = self.stations_near(self.input.src[i]);
+            let b = self.stations_near(self.input.dst[i]);
+            if a.iter().any(|x| b.contains(x)) {
+                income += dist(self.input.src[i], self.input.dst[i]) as i64;
+            }
+        }
+        income
+    }
+
+    /// p からマンハッタン距離 2 以内にある駅の連結成分
+    fn stations_near(&mut self, p: (usize, usize)) -> Vec<usize> {
+        let n = self.input.n as i64;
+        let mut ret = vec![];
+        for dr in -2..=2i64 {
+            for dc in -2..=2i64 {
+                let (r, c) = (p.0 as i64 + dr, p.1 as i64 + dc);
+                if dr.abs() + dc.abs() > 2 || r < 0 || r >= n || c < 0 || c >= n {
+                    continue;
+                }
+                let (r, c) = (r as usize, c as usize);
+                if self.state.grid_state[r][c] == Some(0) {
+                    ret.push(self.uf.find(r * self.input.n + c));
+                }
+            }
+        }
+        ret
+    }
+}
+
+/// 出力を最後まで再生した結果
+///
+/// 盤面は建設を順に適用するだけで復元できるので、資金と収入の推移だけを保存しておく
+pub struct Replay {
+    pub out: Vec<Action>,
+    /// money[t], income[t] は t ターン後の資金と収入
+    pub money: Vec<i64>,
+    pub income: Vec<i64>,
+    pub score: i64,
+    pub err: Option<RailError>,
+}
+
+impl Replay {
+    pub fn new(input: &Input, out: Vec<Action>) -> Replay {
+        let mut sim = Sim::new(input);
+        let mut money = vec![sim.state.money];
+        let mut income = vec![0];
+        let mut err = None;
+        // 出力が T 行に満たない場合、残りのターンは何もしない
+        for t in 0..input.t {
+            if let Err(e) = sim.step(out.get(t).copied().unwrap_or(Action::Wait)) {
+                err = Some(e);
+                break;
+            }
+            money.push(sim.state.money);
+            income.push(sim.state.income);
+        }
+        let score = if err.is_none() { sim.state.money } else { 0 };
+        Replay {
+            out,
+            money,
+            income,
+            score,
+            err,
+        }
+    }
+
+    /// turn ターン後の状態 (不正な建設があった場合はその直前で止まる)
+    pub fn state(&self, input: &Input, turn: usize) -> State {
+        let turn = turn.min(self.money.len() - 1);
+        let mut grid_state = vec![vec![None; input.n]; input.n];
+        for a in &self.out[..turn.min(self.out.len())] {
+            if let &Action::Build(p, r, c) = a {
+                grid_state[r][c] = Some(p);
+            }
+        }
+        State {
+            grid_state,
+            money: self.money[turn],
+            income: self.income[turn],
+            turn,
+        }
+    }
+}
+
+pub fn render(input: &Input, state: State) -> String {
+    let vis_data = VisData { state };
+    let mut doc = vis::new_svg();
+    doc = vis::define_rails(doc);
+    doc = vis::draw_grid(input, doc);
+    doc = vis::draw_station_range(&vis_data, input, doc);
+    doc = vis::draw_rails(&vis_data, input, doc);
+    doc = vis::draw_source(input, doc);
+    doc = vis::draw_destination(input, doc);
+    doc = vis::draw_tooltips(&vis_data, input, doc);
+    doc.to_string()
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Rail;
+
+impl Problem for Rail {
+    type Input = Input;
+    type Output = Replay;
+    type Error = RailError;
+
+    fn generate(&self, seed: u64, params: &GenParams) -> Result<Input, String> {
+        if *params != GenParams::default() {
+            return Err("rail does not support generator parameters".to_owned());
+        }
+        Ok(gen(seed))
+    }
+
+    fn parse_input(&self, f: &str) -> Result<Input, String> {
+        parse_input(f).map_err(|e| e.to_string())
+    }
+
+    fn parse_output(&self, input: &Input, f: &str, _opts: &Options) -> Result<Replay, RailError> {
+        parse_output(input, f).map(|out| Replay::new(input, out.out))
+    }
+
+    fn score(&self, _input: &Input, out: &Replay) -> (i64, Option<RailError>) {
+        (out.score, out.err.clone())
+    }
+
+    /// 出力が T 行に満たなくても残りのターンは何もせずに進むので、常に T ターン
+    fn max_turn(&self, input: &Input, _out: &Replay) -> usize {
+        input.t
+    }
+
+    fn render(&self, input: &Input, out: Option<&Replay>, turn: usize, _view: &View) -> String {
+        let state = match out {
+            Some(replay) => replay.state(input, turn),
+            None => Sim::new(input).state,
+        };
+        render(input, state)
+    }
+
+    fn breakdown(&self, input: &Input, out: Option<&Replay>) -> (String, String) {
+        let Some(replay) = out else {
+            return (String::new(), "{}".to_owned());
+        };
+        let last = replay.state(input, input.t);
+        let stations = last
+            .grid_state
+            .iter()
+            .flatten()
+            .filter(|&&v| v == Some(0))
+            .count();
+        let rails = last
+            .grid_state
+            .iter()
+            .flatten()
+            .filter(|v| matches!(v, Some(1..)))
+            .count();
+        (
+            format!(
+                "money = {}\nincome = {} / turn\nstations = {}\nrails = {}",
+                last.money, last.income, stations, rails
+            ),
+            format!(
+                "{{\"money\":{},\"income\":{},\"stations\":{},\"rails\":{}}}",
+                last.money, last.income, stations, rails
+            ),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_input_rejects_values_over_limits() {
+        for (head, col) in [
+            ("51 1 11000 800", 1),
+            ("50 1601 11000 800", 4),
+            ("50 1 20001 800", 6),
+            ("50 1 11000 801", 12),
+        ] {
+            let err = parse_input(&format!("{}\n0 0 5 5\n", head)).unwrap_err();
+            assert_eq!((err.line, err.col), (1, Some(col)), "{}", head);
+        }
+        assert!(parse_input("50 1 11000 800\n0 0 5 5\n").is_ok());
+    }
+
+    #[test]
+    fn max_turn_is_t_for_short_output() {
+        let input = parse_input("5 1 11000 10\n0 0 4 4\n").unwrap();
+        let out = Rail
+            .parse_output(&input, "-1\n-1\n", &Options::default())
+            .unwrap();
+        assert_eq!(Rail.max_turn(&input, &out), 10);
+        assert_eq!(out.money.len(), 11);
+        assert_eq!(out.state(&input, 10).turn, 10);
+    }
+}
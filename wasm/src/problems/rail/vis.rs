@@ -1,10 +1,7 @@
-use svg::node::element::{
-    Circle, Definitions, Group, Line, Path, Rectangle, Title, Use, SVG,
-};
-use svg::node::Text;
+use svg::node::element::{Circle, Definitions, Group, Line, Path, Rectangle, Use, SVG};
 
-use crate::kind_v_to_usize;
-use crate::{Input, VisData};
+use super::{kind_v_to_usize, Input, VisData};
+use crate::problems::draw::create_rectangle;
 
 const W: f64 = 800.0;
 const H: f64 = 800.0;
@@ -81,7 +78,7 @@ fn draw_rail(rail_type: &str, theta: f64, x: f64, y: f64, ratio: f64, doc: SVG)
 }
 
 pub fn draw_rails(vis_data: &VisData, input: &Input, mut doc: SVG) -> SVG {
-    let ratio = H as f64 / input.n as f64 / 30.0;
+    let ratio = H / input.n as f64 / 30.0;
     for i in 0..input.n {
         for j in 0..input.n {
             let x = j as f64 * W / input.n as f64;
@@ -146,7 +143,7 @@ pub fn draw_station_range(vis_data: &VisData, input: &Input, mut doc: SVG) -> SV
 }
 
 pub fn draw_source(input: &Input, mut doc: SVG) -> SVG {
-    let ratio = H as f64 / input.n as f64 / 30.0;
+    let ratio = H / input.n as f64 / 30.0;
     for i in 0..input.m {
         let x = input.src[i].1 as f64 * H / input.n as f64;
         let y = input.src[i].0 as f64 * W / input.n as f64;
@@ -163,7 +160,7 @@ pub fn draw_source(input: &Input, mut doc: SVG) -> SVG {
 }
 
 pub fn draw_destination(input: &Input, mut doc: SVG) -> SVG {
-    let ratio = H as f64 / input.n as f64 / 30.0;
+    let ratio = H / input.n as f64 / 30.0;
     for i in 0..input.m {
         let x = input.dst[i].1 as f64 * H / input.n as f64;
         let y = input.dst[i].0 as f64 * W / input.n as f64;
@@ -193,13 +190,11 @@ pub fn draw_tooltips(vis_data: &VisData, input: &Input, mut doc: SVG) -> SVG {
         for c in 0..input.n {
             let mut statement = vec![format!("({}, {})", r, c)];
             let kind = kind_v_to_usize(&vis_data.state.grid_state[r][c]);
-            match kind{
+            match kind {
                 0 => {
                     statement.push("Station".to_string());
                 }
-                1 | 2 | 3 | 4 | 5 | 6 => {
-                    statement.push(format!("Rail {}", kind))
-                }
+                1..=6 => statement.push(format!("Rail {}", kind)),
                 usize::MAX => {
                     statement.push("Empty".to_string());
                 }
@@ -224,12 +219,9 @@ pub fn draw_tooltips(vis_data: &VisData, input: &Input, mut doc: SVG) -> SVG {
             if !dst_v.is_empty() {
                 statement.push(format!("workplace: {}", dst_v.join(",")));
             }
-            let mut all_v = src_v.clone();
-            all_v.extend(dst_v.clone());
-
-            let mut rect = create_rectangle(
-                c as i64 * W as i64 / input.n as i64,
-                r as i64 * H as i64 / input.n as i64,
+            let rect = create_rectangle(
+                (c as i64 * W as i64 / input.n as i64) as f64,
+                (r as i64 * H as i64 / input.n as i64) as f64,
                 W / input.n as f64,
                 H / input.n as f64,
                 "gray",
@@ -238,9 +230,6 @@ pub fn draw_tooltips(vis_data: &VisData, input: &Input, mut doc: SVG) -> SVG {
                 0.0,
                 Some(statement.join("\n")),
             );
-            rect = rect.set("onmouseover", format!("showHighlight([{}])", all_v.join(",")));
-            rect = rect.set("onmouseleave", format!("clearHighlight([{}])", all_v.join(",")));
-            rect = rect.set("class", format!("{}", all_v.iter().map(|v| "p_".to_string() + v ).collect::<Vec<_>>().join(" ")));
             doc = doc.add(rect);
         }
     }
@@ -248,35 +237,6 @@ pub fn draw_tooltips(vis_data: &VisData, input: &Input, mut doc: SVG) -> SVG {
     doc
 }
 
-fn create_rectangle(
-    x: i64,
-    y: i64,
-    width: f64,
-    height: f64,
-    fill: &str,
-    fill_opacity: f64,
-    stroke: Option<&str>,
-    stroke_width: f64,
-    title: Option<String>,
-) -> Rectangle {
-    let mut rect = Rectangle::new()
-        .set("x", x as f64)
-        .set("y", y as f64)
-        .set("width", width)
-        .set("height", height)
-        .set("fill", fill)
-        .set("fill-opacity", fill_opacity);
-    if let Some(stroke) = stroke {
-        rect = rect.set("stroke", stroke).set("stroke-width", stroke_width);
-    }
-
-    if let Some(title) = title {
-        rect = rect.add(Title::new("animals").add(Text::new(title)));
-    }
-
-    rect
-}
-
 fn add_line(
     g: Group,
     x1: f64,
@@ -298,7 +258,7 @@ fn add_line(
 }
 
 pub fn define_rails(doc: SVG) -> SVG {
-    let ratio = H as f64 / 50.0 / 30.0;
+    let ratio = H / 50.0 / 30.0;
     let mut rail1 = Group::new().set("id", "rail1").set("stroke_width", 2.0);
     let rail1_stroke_width = 0.8;
     let rail1_stroke = "silver";
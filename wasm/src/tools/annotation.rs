@@ -0,0 +1,59 @@
+use super::read;
+
+/// 出力中のコメント行から得られる、ある操作に対する注釈
+///
+/// - `#c text`: その操作の説明として text を表示する
+/// - `#h i j color`: その操作の後の盤面でマス (i, j) を color で強調する
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Annotation {
+    pub comments: Vec<String>,
+    pub highlights: Vec<((usize, usize), String)>,
+}
+
+impl Annotation {
+    pub fn is_empty(&self) -> bool {
+        self.comments.is_empty() && self.highlights.is_empty()
+    }
+
+    /// `#` で始まるコメント行を読み込む (`#c`・`#h` 以外のコメントは無視する)
+    ///
+    /// 形式が正しくない `#h` の行は読み飛ばし、その理由を返す (出力全体のパースは失敗させない)
+    pub fn add_line(
+        &mut self,
+        N: usize,
+        line: &str,
+        tokens: &[(usize, &str)],
+        line_no: usize,
+    ) -> Result<(), String> {
+        match tokens[0].1 {
+            "#c" => {
+                let text = line.trim_start()[2..].trim();
+                self.comments.push(text.to_owned());
+            }
+            "#h" => {
+                let err = |e| format!("line {}: ignored #h ({})", line_no, e);
+                let get = |k: usize| tokens.get(k).map(|t| t.1);
+                let i = read(get(1), 0..N).map_err(err)?;
+                let j = read(get(2), 0..N).map_err(err)?;
+                let Some(color) = get(3) else {
+                    return Err(err("Missing color".to_owned()));
+                };
+                // SVG の属性にそのまま埋め込むので、色の指定に使う文字だけを許す
+                if !color
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "#(),.%".contains(c))
+                {
+                    return Err(err(format!("Invalid color: {}", color)));
+                }
+                self.highlights.push(((i, j), color.to_owned()));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// 表示用のテキスト (`#c` の内容を1行ずつ並べたもの)
+    pub fn text(&self) -> String {
+        self.comments.join("\n")
+    }
+}
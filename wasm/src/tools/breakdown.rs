@@ -0,0 +1,128 @@
+#![allow(non_snake_case)]
+
+use super::{json_str, Action, Input, StepResult};
+
+/// スコアの内訳
+#[derive(Clone, Debug)]
+pub struct Breakdown {
+    /// 荷物の総数
+    pub K: usize,
+    /// 正しい穴に運ばれた荷物の数
+    pub A: usize,
+    /// 色ごとの (色, 荷物の数, 正しい穴に運ばれた数)
+    pub colors: Vec<(char, usize, usize)>,
+    /// 違う色の穴に落ちた荷物の数 (岩は含まない)
+    pub lost: usize,
+    pub moves: usize,
+    pub carries: usize,
+    pub rolls: usize,
+    /// 全ての荷物を運び、操作回数に応じたボーナスの式が適用されたか
+    pub full_clear: bool,
+    /// 出力が不正な場合に、正当な部分 (最初の不正な操作の直前まで) だけを採点したときのスコア
+    pub prefix_score: Option<i64>,
+}
+
+impl Breakdown {
+    pub fn new(input: &Input) -> Breakdown {
+        let mut colors: Vec<(char, usize, usize)> = vec![];
+        for row in &input.cs {
+            for &c in row {
+                if c.is_ascii_lowercase() {
+                    match colors.iter_mut().find(|e| e.0 == c) {
+                        Some(e) => e.1 += 1,
+                        None => colors.push((c, 1, 0)),
+                    }
+                }
+            }
+        }
+        colors.sort();
+        Breakdown {
+            K: colors.iter().map(|e| e.1).sum(),
+            A: 0,
+            colors,
+            lost: 0,
+            moves: 0,
+            carries: 0,
+            rolls: 0,
+            full_clear: false,
+            prefix_score: None,
+        }
+    }
+
+    /// 1回の操作の結果を反映する
+    pub fn add(&mut self, step: &StepResult) {
+        match step.action {
+            Action::Move(_) => self.moves += 1,
+            Action::Carry(_) => self.carries += 1,
+            Action::Roll(_) => self.rolls += 1,
+        }
+        if let (Some(item), Some(_)) = (step.item, step.hole) {
+            if step.delivered() {
+                self.A += 1;
+                if let Some(e) = self.colors.iter_mut().find(|e| e.0 == item) {
+                    e.2 += 1;
+                }
+            } else if item != '@' {
+                self.lost += 1;
+            }
+        }
+        self.full_clear = self.A == self.K;
+    }
+
+    pub fn actions(&self) -> usize {
+        self.moves + self.carries + self.rolls
+    }
+
+    pub fn to_json(&self) -> String {
+        let colors = self
+            .colors
+            .iter()
+            .map(|&(c, k, a)| format!("{}:{{\"K\":{},\"A\":{}}}", json_str(&c.to_string()), k, a))
+            .collect::<Vec<_>>();
+        format!(
+            "{{\"K\":{},\"A\":{},\"colors\":{{{}}},\"lost\":{},\"moves\":{},\"carries\":{},\"rolls\":{},\"full_clear\":{},\"prefix_score\":{}}}",
+            self.K,
+            self.A,
+            colors.join(","),
+            self.lost,
+            self.moves,
+            self.carries,
+            self.rolls,
+            self.full_clear,
+            self.prefix_score
+                .map_or("null".to_owned(), |s| s.to_string())
+        )
+    }
+}
+
+impl std::fmt::Display for Breakdown {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "delivered = {} / {}", self.A, self.K)?;
+        for &(c, k, a) in &self.colors {
+            writeln!(f, "  {}: {} / {}", c, a, k)?;
+        }
+        writeln!(f, "lost = {}", self.lost)?;
+        writeln!(
+            f,
+            "actions = {} (move {}, carry {}, roll {})",
+            self.actions(),
+            self.moves,
+            self.carries,
+            self.rolls
+        )?;
+        if self.full_clear {
+            write!(f, "full clear: 1e6 * (1 + log2(1e4 / {}))", self.actions())?;
+        } else {
+            write!(f, "partial: 1e6 * {} / {}", self.A, self.K)?;
+        }
+        if let Some(score) = self.prefix_score {
+            write!(
+                f,
+                "\nscore of the legal prefix ({} actions) = {}",
+                self.actions(),
+                score
+            )?;
+        }
+        Ok(())
+    }
+}
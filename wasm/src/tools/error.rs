@@ -0,0 +1,145 @@
+/// 出力が不正だった理由
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ErrorKind {
+    OutOfBoard,
+    NoItemToCarry,
+    NoItemToRoll,
+    Collision,
+    TooManyActions,
+    ParseError,
+}
+
+impl ErrorKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ErrorKind::OutOfBoard => "OutOfBoard",
+            ErrorKind::NoItemToCarry => "NoItemToCarry",
+            ErrorKind::NoItemToRoll => "NoItemToRoll",
+            ErrorKind::Collision => "Collision",
+            ErrorKind::TooManyActions => "TooManyActions",
+            ErrorKind::ParseError => "ParseError",
+        }
+    }
+}
+
+/// 出力のパースやシミュレーションで発生したエラー
+///
+/// Display では従来どおりの文字列 (例: `Collision (turn 12)`) を出力する
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScoreError {
+    pub kind: ErrorKind,
+    /// エラーが発生した操作の番号 (0-indexed)
+    pub turn: usize,
+    /// 操作前のプレイヤーの位置 (パースエラーでは None)
+    pub pos: Option<(usize, usize)>,
+    /// 移動先のマス (盤面外の場合は None)
+    pub target: Option<(usize, usize)>,
+    /// パースエラーの詳細
+    pub msg: String,
+    /// パースエラーが発生した出力の行番号 (1-indexed)
+    pub line: Option<usize>,
+    /// パースできなかったトークン
+    pub token: Option<String>,
+}
+
+impl ScoreError {
+    pub fn new(
+        kind: ErrorKind,
+        turn: usize,
+        pos: (usize, usize),
+        target: Option<(usize, usize)>,
+    ) -> ScoreError {
+        ScoreError {
+            kind,
+            turn,
+            pos: Some(pos),
+            target,
+            msg: String::new(),
+            line: None,
+            token: None,
+        }
+    }
+
+    pub fn parse(turn: usize, msg: String) -> ScoreError {
+        ScoreError {
+            kind: ErrorKind::ParseError,
+            turn,
+            pos: None,
+            target: None,
+            msg,
+            line: None,
+            token: None,
+        }
+    }
+
+    /// 出力の line 行目の token でのパースエラー
+    pub fn parse_at(turn: usize, line: usize, token: Option<&str>, msg: String) -> ScoreError {
+        ScoreError {
+            line: Some(line),
+            token: token.map(|t| t.to_owned()),
+            ..ScoreError::parse(turn, msg)
+        }
+    }
+
+    pub fn too_many_actions(turn: usize) -> ScoreError {
+        ScoreError {
+            kind: ErrorKind::TooManyActions,
+            turn,
+            pos: None,
+            target: None,
+            msg: String::new(),
+            line: None,
+            token: None,
+        }
+    }
+}
+
+impl std::fmt::Display for ScoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let t = self.turn;
+        match self.kind {
+            ErrorKind::OutOfBoard => write!(f, "Out of the board (turn {t})"),
+            ErrorKind::NoItemToCarry => write!(f, "No item to carry (turn {t})"),
+            ErrorKind::NoItemToRoll => write!(f, "No item to roll (turn {t})"),
+            ErrorKind::Collision => write!(f, "Collision (turn {t})"),
+            ErrorKind::TooManyActions => write!(f, "Too many actions"),
+            ErrorKind::ParseError => match self.line {
+                Some(line) => write!(f, "line {}: {}", line, self.msg),
+                None => write!(f, "{}", self.msg),
+            },
+        }
+    }
+}
+
+impl std::error::Error for ScoreError {}
+
+/// 入力ファイルの形式の誤りや制約の違反
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InputError {
+    /// 行番号 (1-indexed)
+    pub line: usize,
+    /// 列番号 (1-indexed、行全体に関するエラーでは None)
+    pub col: Option<usize>,
+    pub msg: String,
+}
+
+impl InputError {
+    pub fn new(line: usize, col: Option<usize>, msg: impl Into<String>) -> InputError {
+        InputError {
+            line,
+            col,
+            msg: msg.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for InputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.col {
+            Some(col) => write!(f, "line {}, col {}: {}", self.line, col, self.msg),
+            None => write!(f, "line {}: {}", self.line, self.msg),
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
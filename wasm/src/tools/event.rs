@@ -0,0 +1,187 @@
+use super::{json_str, Action, StepResult};
+
+/// 盤面上で起きた出来事
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// 荷物を持ち上げた
+    PickedUp {
+        turn: usize,
+        item: char,
+        at: (usize, usize),
+    },
+    /// 荷物を隣のマスへ運んだ
+    Carried {
+        turn: usize,
+        item: char,
+        from: (usize, usize),
+        to: (usize, usize),
+    },
+    /// 荷物を転がした (to は止まったマスまたは落ちた穴)
+    Rolled {
+        turn: usize,
+        item: char,
+        from: (usize, usize),
+        to: (usize, usize),
+    },
+    /// 荷物が同じ色の穴に落ちた
+    Delivered {
+        turn: usize,
+        item: char,
+        hole: char,
+        at: (usize, usize),
+    },
+    /// 荷物または岩が違う色の穴に落ちた
+    Swallowed {
+        turn: usize,
+        item: char,
+        hole: char,
+        at: (usize, usize),
+    },
+    /// 岩を動かした
+    RockMoved {
+        turn: usize,
+        from: (usize, usize),
+        to: (usize, usize),
+    },
+}
+
+impl Event {
+    /// 1回の操作で起きた出来事を発生順に列挙する
+    pub fn from_step(step: &StepResult) -> Vec<Event> {
+        let mut events = vec![];
+        let Some(item) = step.item else {
+            return events;
+        };
+        let turn = step.turn;
+        let (from, to) = (step.from, step.item_to);
+        if item == '@' {
+            events.push(Event::RockMoved { turn, from, to });
+        } else if matches!(step.action, Action::Carry(_)) {
+            events.push(Event::PickedUp {
+                turn,
+                item,
+                at: from,
+            });
+            events.push(Event::Carried {
+                turn,
+                item,
+                from,
+                to,
+            });
+        } else {
+            events.push(Event::Rolled {
+                turn,
+                item,
+                from,
+                to,
+            });
+        }
+        if let Some(hole) = step.hole {
+            if step.delivered() {
+                events.push(Event::Delivered {
+                    turn,
+                    item,
+                    hole,
+                    at: to,
+                });
+            } else {
+                events.push(Event::Swallowed {
+                    turn,
+                    item,
+                    hole,
+                    at: to,
+                });
+            }
+        }
+        events
+    }
+
+    pub fn turn(&self) -> usize {
+        match *self {
+            Event::PickedUp { turn, .. }
+            | Event::Carried { turn, .. }
+            | Event::Rolled { turn, .. }
+            | Event::Delivered { turn, .. }
+            | Event::Swallowed { turn, .. }
+            | Event::RockMoved { turn, .. } => turn,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        let p = |(i, j): (usize, usize)| format!("[{},{}]", i, j);
+        let c = |c: char| json_str(&c.to_string());
+        match *self {
+            Event::PickedUp { turn, item, at } => format!(
+                "{{\"turn\":{},\"type\":\"PickedUp\",\"item\":{},\"at\":{}}}",
+                turn,
+                c(item),
+                p(at)
+            ),
+            Event::Carried {
+                turn,
+                item,
+                from,
+                to,
+            } => format!(
+                "{{\"turn\":{},\"type\":\"Carried\",\"item\":{},\"from\":{},\"to\":{}}}",
+                turn,
+                c(item),
+                p(from),
+                p(to)
+            ),
+            Event::Rolled {
+                turn,
+                item,
+                from,
+                to,
+            } => format!(
+                "{{\"turn\":{},\"type\":\"Rolled\",\"item\":{},\"from\":{},\"to\":{}}}",
+                turn,
+                c(item),
+                p(from),
+                p(to)
+            ),
+            Event::Delivered {
+                turn,
+                item,
+                hole,
+                at,
+            } => format!(
+                "{{\"turn\":{},\"type\":\"Delivered\",\"item\":{},\"hole\":{},\"at\":{}}}",
+                turn,
+                c(item),
+                c(hole),
+                p(at)
+            ),
+            Event::Swallowed {
+                turn,
+                item,
+                hole,
+                at,
+            } => format!(
+                "{{\"turn\":{},\"type\":\"Swallowed\",\"item\":{},\"hole\":{},\"at\":{}}}",
+                turn,
+                c(item),
+                c(hole),
+                p(at)
+            ),
+            Event::RockMoved { turn, from, to } => format!(
+                "{{\"turn\":{},\"type\":\"RockMoved\",\"from\":{},\"to\":{}}}",
+                turn,
+                p(from),
+                p(to)
+            ),
+        }
+    }
+}
+
+pub fn events_to_json(events: &[Event]) -> String {
+    format!(
+        "[{}]",
+        events
+            .iter()
+            .map(|e| e.to_json())
+            .collect::<Vec<_>>()
+            .join(",")
+    )
+}
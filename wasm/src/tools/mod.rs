@@ -1,10 +1,26 @@
-#![allow(non_snake_case, unused_macros)]
+#![allow(non_snake_case, unused_macros, clippy::needless_range_loop)]
 
 use noise::{NoiseFn, Perlin};
-use proconio::{input, marker::Chars};
 use rand::prelude::*;
 use std::ops::RangeBounds;
+pub mod annotation;
+pub mod breakdown;
+pub mod error;
+pub mod event;
+pub mod params;
+pub mod replay;
 pub mod score;
+pub mod sim;
+pub mod solvability;
+
+pub use annotation::Annotation;
+pub use breakdown::Breakdown;
+pub use error::{ErrorKind, InputError, ScoreError};
+pub use event::Event;
+pub use params::GenParams;
+pub use replay::Replay;
+pub use sim::{Sim, StepResult};
+pub use solvability::Solvability;
 
 pub trait SetMinMax {
     fn setmin(&mut self, v: Self) -> bool;
@@ -36,65 +52,26 @@ macro_rules! mat {
 	($e:expr; $d:expr $(; $ds:expr)+) => { Vec::from(vec![mat![$e $(; $ds)*]; $d]) };
 }
 
-pub fn get_grid(input: &Input, out: &[Action]) -> (Vec<Vec<char>>, (usize, usize)) {
-    let mut cs = input.cs.clone();
-    let mut pos = (0, 0);
-    for i in 0..input.N {
-        for j in 0..input.N {
-            if cs[i][j] == 'A' {
-                pos = (i, j);
-            }
-        }
-    }
-    for t in 0..out.len() {
-        match out[t] {
-            Action::Move(d) => {
-                let (di, dj) = DIJ[d];
-                pos.0 += di;
-                pos.1 += dj;
-            }
-            Action::Carry(d) => {
-                let (di, dj) = DIJ[d];
-                let c = cs[pos.0][pos.1];
-                cs[pos.0][pos.1] = '.';
-                pos.0 += di;
-                pos.1 += dj;
-                if matches!(cs[pos.0][pos.1], 'A'..='Z') {
-                } else {
-                    assert_eq!(cs[pos.0][pos.1], '.');
-                    cs[pos.0][pos.1] = c;
-                }
-            }
-            Action::Roll(d) => {
-                let (di, dj) = DIJ[d];
-                let c = cs[pos.0][pos.1];
-                cs[pos.0][pos.1] = '.';
-                let mut crt = pos;
-                loop {
-                    let next = (crt.0 + di, crt.1 + dj);
-                    if next.0 >= input.N
-                        || next.1 >= input.N
-                        || matches!(cs[next.0][next.1], '@' | 'a'..='z')
-                    {
-                        cs[crt.0][crt.1] = c;
-                        break;
-                    } else if matches!(cs[next.0][next.1], 'A'..='Z') {
-                        break;
-                    } else {
-                        crt = next;
-                    }
-                }
-            }
+/// out を先頭から実行した後の盤面とプレイヤーの位置
+///
+/// 不正な操作があった場合はその直前の盤面で止まり、エラー (失敗したターンを含む) を返す
+pub fn get_grid(
+    input: &Input,
+    out: &[Action],
+) -> (Vec<Vec<char>>, (usize, usize), Option<ScoreError>) {
+    let mut sim = Sim::new(input);
+    for &a in out {
+        if let Err(err) = sim.step(a) {
+            return (sim.cs, sim.pos, Some(err));
         }
     }
-
-    (cs, pos)
+    (sim.cs, sim.pos, None)
 }
 
 #[derive(Clone, Debug)]
 pub struct Input {
-    N: usize,
-    M: usize,
+    pub N: usize,
+    pub M: usize,
     pub cs: Vec<Vec<char>>,
 }
 
@@ -108,14 +85,118 @@ impl std::fmt::Display for Input {
     }
 }
 
-pub fn parse_input(f: &str) -> Input {
-    let f = proconio::source::once::OnceSource::from(f);
-    input! {
-        from f,
-        N: usize, M: usize,
-        cs: [Chars; N],
+/// 行を空白で区切り、(列番号 (1-indexed), トークン) の列にする
+pub fn tokens(line: &str) -> Vec<(usize, &str)> {
+    let mut ret = vec![];
+    let mut start = None;
+    for (j, c) in line.char_indices().chain([(line.len(), ' ')]) {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some(j),
+            (true, Some(s)) => {
+                ret.push((line[..s].chars().count() + 1, &line[s..j]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    ret
+}
+
+/// 入力をパースし、形式と制約を検査する
+///
+/// - 1行目は N M (1 ≤ M ≤ 26)
+/// - 続く N 行はそれぞれ N 文字で、`.`・`@`・英大文字 (穴)・英小文字 (荷物) のみからなる
+/// - 開始位置の 'A' はちょうど1つ
+/// - 穴の色の種類数は M に等しく、荷物にはすべて同じ色の穴がある
+pub fn parse_input(f: &str) -> Result<Input, InputError> {
+    let lines = f.lines().map(|l| l.trim_end()).collect::<Vec<_>>();
+    let head = tokens(lines.first().copied().unwrap_or(""));
+    let N = read_token(&head, 0, 1, 1..=1000)?;
+    let M = read_token(&head, 1, 1, 1..=26)?;
+    if let Some(&(col, t)) = head.get(2) {
+        return Err(InputError::new(
+            1,
+            Some(col),
+            format!("Unexpected token: {}", t),
+        ));
+    }
+    let mut cs = vec![];
+    let mut start = None;
+    for i in 0..N {
+        let line = i + 2;
+        let Some(row) = lines.get(i + 1) else {
+            return Err(InputError::new(
+                line,
+                None,
+                format!("Unexpected EOF: expected {} rows but found {}", N, i),
+            ));
+        };
+        let row = row.chars().collect::<Vec<_>>();
+        for (j, &c) in row.iter().enumerate() {
+            if !matches!(c, '.' | '@' | 'A'..='Z' | 'a'..='z') {
+                return Err(InputError::new(
+                    line,
+                    Some(j + 1),
+                    format!("Illegal character: {:?}", c),
+                ));
+            }
+            if c == 'A' {
+                if start.is_some() {
+                    return Err(InputError::new(
+                        line,
+                        Some(j + 1),
+                        "Multiple start cells 'A'",
+                    ));
+                }
+                start = Some((i, j));
+            }
+        }
+        if row.len() != N {
+            return Err(InputError::new(
+                line,
+                None,
+                format!("Row has {} cells but N = {}", row.len(), N),
+            ));
+        }
+        cs.push(row);
+    }
+    if let Some(k) = (N + 1..lines.len()).find(|&k| !lines[k].is_empty()) {
+        return Err(InputError::new(
+            k + 1,
+            Some(1),
+            "Unexpected content after the grid",
+        ));
+    }
+    if start.is_none() {
+        return Err(InputError::new(2, None, "No start cell 'A'"));
+    }
+    let mut holes = [false; 26];
+    for &c in cs.iter().flatten() {
+        if c.is_ascii_uppercase() {
+            holes[(c as u8 - b'A') as usize] = true;
+        }
+    }
+    let count = holes.iter().filter(|&&h| h).count();
+    if count != M {
+        return Err(InputError::new(
+            1,
+            head.get(1).map(|t| t.0),
+            format!("M = {} but the grid has {} hole colors", M, count),
+        ));
+    }
+    for i in 0..N {
+        for j in 0..N {
+            let c = cs[i][j];
+            if c.is_ascii_lowercase() && !holes[(c as u8 - b'a') as usize] {
+                return Err(InputError::new(
+                    i + 2,
+                    Some(j + 1),
+                    format!("Item '{}' has no hole '{}'", c, c.to_ascii_uppercase()),
+                ));
+            }
+        }
     }
-    Input { N, M, cs }
+    Ok(Input { N, M, cs })
 }
 
 pub fn read<T: Copy + PartialOrd + std::fmt::Display + std::str::FromStr, R: RangeBounds<T>>(
@@ -137,6 +218,38 @@ pub fn read<T: Copy + PartialOrd + std::fmt::Display + std::str::FromStr, R: Ran
     }
 }
 
+/// `tokens` で区切った line 行目の k 番目のトークンを読む (エラーには行番号と列番号を付ける)
+pub fn read_token<
+    T: Copy + PartialOrd + std::fmt::Display + std::str::FromStr,
+    R: RangeBounds<T>,
+>(
+    tokens: &[(usize, &str)],
+    k: usize,
+    line: usize,
+    range: R,
+) -> Result<T, InputError> {
+    read(tokens.get(k).map(|t| t.1), range)
+        .map_err(|e| InputError::new(line, tokens.get(k).map(|t| t.0), e))
+}
+
+/// JSON の文字列リテラルとしてエスケープする
+pub fn json_str(s: &str) -> String {
+    let mut ret = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => ret += "\\\"",
+            '\\' => ret += "\\\\",
+            '\n' => ret += "\\n",
+            '\r' => ret += "\\r",
+            '\t' => ret += "\\t",
+            c if (c as u32) < 0x20 => ret += &format!("\\u{:04x}", c as u32),
+            c => ret.push(c),
+        }
+    }
+    ret.push('"');
+    ret
+}
+
 #[derive(Clone, Debug, Copy)]
 pub enum Action {
     Move(usize),
@@ -144,41 +257,190 @@ pub enum Action {
     Roll(usize),
 }
 
+impl Action {
+    /// 操作の種類の名前
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Move(_) => "move",
+            Action::Carry(_) => "carry",
+            Action::Roll(_) => "roll",
+        }
+    }
+}
+
+/// 出力と同じ `2 L` の形式
+impl std::fmt::Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (k, d) = match *self {
+            Action::Move(d) => (1, d),
+            Action::Carry(d) => (2, d),
+            Action::Roll(d) => (3, d),
+        };
+        write!(f, "{} {}", k, DIR[d])
+    }
+}
+
 const DIJ: [(usize, usize); 4] = [(!0, 0), (1, 0), (0, !0), (0, 1)];
 const DIR: [char; 4] = ['U', 'D', 'L', 'R'];
 
+/// 操作の数の上限
+pub const MAX_ACTIONS: usize = 10000;
+
+#[derive(Clone, Debug)]
 pub struct Output {
     pub out: Vec<Action>,
+    /// annotations[t] は t 番目の操作に付けられた注釈
+    pub annotations: Vec<Annotation>,
+    /// 読み飛ばした注釈の行についての警告
+    pub warnings: Vec<String>,
+}
+
+/// 出力をパースする
+///
+/// - `#` で始まる行はコメントとして読み飛ばす (`#c`・`#h` は直後の操作の注釈とする)
+/// - 形式が正しくない `#h` の行はエラーにせず、警告 (Output::warnings) を残して無視する
+/// - 先頭に操作の数だけを書いた行があってもよい (その場合は実際の操作の数と一致している必要がある)
+/// - 操作の種類と方向は空白と改行のどちらで区切ってもよい (1行に複数の操作を書いても、`1` と `U` を別の行に書いてもよい)
+/// - 操作の数が MAX_ACTIONS を超える場合はエラー
+#[allow(clippy::result_large_err)]
+pub fn parse_output(input: &Input, f: &str) -> Result<Output, ScoreError> {
+    let out = parse_output_unlimited(input, f)?;
+    if out.out.len() > MAX_ACTIONS {
+        return Err(ScoreError::too_many_actions(MAX_ACTIONS));
+    }
+    Ok(out)
 }
 
-pub fn parse_output(_input: &Input, f: &str) -> Result<Output, String> {
+/// 操作の数の上限を確認せずに出力をパースする
+///
+/// 上限を超えた出力も最後まで再生したい場合に使う (上限の判定は Replay が行う)
+#[allow(clippy::result_large_err)]
+pub fn parse_output_unlimited(input: &Input, f: &str) -> Result<Output, ScoreError> {
+    let lines = f
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line, tokens(line)))
+        .filter(|(_, _, ts)| !ts.is_empty())
+        .collect::<Vec<_>>();
+    let is_comment = |ts: &[(usize, &str)]| ts[0].1.starts_with('#');
+    // 操作は2トークンずつなので、操作の数の行がある場合に限りトークンの総数が奇数になる
+    // (`1` と `U` を別の行に書いた出力の `1` を操作の数と取り違えないようにする)
+    let total = lines
+        .iter()
+        .filter(|(_, _, ts)| !is_comment(ts))
+        .map(|(_, _, ts)| ts.len())
+        .sum::<usize>();
+    let mut count = None;
+    if let Some(&(line_no, _, ref ts)) = lines.iter().find(|(_, _, ts)| !is_comment(ts)) {
+        if ts.len() == 1 && total % 2 == 1 {
+            let token = ts[0].1;
+            let k = read(Some(token), 0..)
+                .map_err(|e| ScoreError::parse_at(0, line_no, Some(token), e))?;
+            count = Some((line_no, token, k));
+        }
+    }
     let mut out = vec![];
-    let mut ss = f.split_whitespace().peekable();
-    while ss.peek().is_some() {
-        let a = read(ss.next(), 1..=3)?;
-        let dir = read(ss.next(), 'A'..='Z')?;
-        let Some(d) = DIR.iter().position(|&x| x == dir) else {
-            return Err(format!("Invalid direction: {}", dir));
-        };
-        out.push(match a {
-            1 => Action::Move(d),
-            2 => Action::Carry(d),
-            3 => Action::Roll(d),
-            _ => unreachable!(),
-        });
+    let mut annotations = vec![];
+    let mut pending = Annotation::default();
+    let mut warnings = vec![];
+    // 方向をまだ読んでいない操作の (種類, 行番号)
+    let mut kind = None;
+    for (line_no, line, ts) in &lines {
+        let line_no = *line_no;
+        if is_comment(ts) {
+            if let Err(e) = pending.add_line(input.N, line, ts, line_no) {
+                warnings.push(e);
+            }
+            continue;
+        }
+        if count.is_some_and(|(l, _, _)| l == line_no) {
+            continue;
+        }
+        for &(_, token) in ts {
+            let t = out.len();
+            let err = |e| ScoreError::parse_at(t, line_no, Some(token), e);
+            let Some((a, _)) = kind.take() else {
+                kind = Some((read(Some(token), 1..=3).map_err(err)?, line_no));
+                continue;
+            };
+            let dir = read(Some(token), 'A'..='Z').map_err(err)?;
+            let Some(d) = DIR.iter().position(|&x| x == dir) else {
+                return Err(err(format!("Invalid direction: {}", dir)));
+            };
+            out.push(match a {
+                1 => Action::Move(d),
+                2 => Action::Carry(d),
+                3 => Action::Roll(d),
+                _ => unreachable!(),
+            });
+            annotations.push(std::mem::take(&mut pending));
+        }
+    }
+    if let Some((_, line)) = kind {
+        return Err(ScoreError::parse_at(
+            out.len(),
+            line,
+            None,
+            "Missing direction".to_owned(),
+        ));
+    }
+    // 最後の操作より後の注釈は最後の操作に付ける
+    if let Some(last) = annotations.last_mut() {
+        last.comments.append(&mut pending.comments);
+        last.highlights.append(&mut pending.highlights);
     }
-    if out.len() > 10000 {
-        return Err("Too many actions".to_owned());
+    if let Some((line, token, k)) = count {
+        if k != out.len() {
+            return Err(ScoreError::parse_at(
+                0,
+                line,
+                Some(token),
+                format!("The number of actions is {} but {} is given", out.len(), k),
+            ));
+        }
     }
-    Ok(Output { out })
+    Ok(Output {
+        out,
+        annotations,
+        warnings,
+    })
 }
 
 pub fn generate(seed: u64, problem: &str) -> Input {
+    generate_with(seed, problem, &GenParams::default()).unwrap()
+}
+
+/// params で盤面の大きさや荷物・岩の数などを変えて入力を生成する
+///
+/// 同じ seed と params からは常に同じ入力が生成される
+pub fn generate_with(seed: u64, problem: &str, params: &GenParams) -> Result<Input, String> {
     let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(seed);
+    params.validate()?;
+    let N = params.N.unwrap_or(20);
+    let M = params.M.unwrap_or(if problem == "B" { 3 } else { 1 });
+    let items = params
+        .items
+        .unwrap_or(if problem == "B" { M * N } else { 2 * N });
+    let rocks = match problem {
+        "A" => params.rocks_or(N, 2 * N),
+        "B" => params.rocks_or(N, 0),
+        _ => params.rocks_or(N, N * N / 2),
+    };
+    if M
+        .checked_add(items)
+        .and_then(|c| c.checked_add(rocks))
+        .is_none_or(|c| c > N * N)
+    {
+        return Err(format!(
+            "Too many cells: {} holes + {} items + {} rocks > {}",
+            M,
+            items,
+            rocks,
+            N * N
+        ));
+    }
     match problem {
         "A" => {
-            let N = 20;
-            let M = 1;
             let mut cs = mat!['.'; N; N];
             let mut ps = vec![];
             for i in 0..N {
@@ -187,22 +449,23 @@ pub fn generate(seed: u64, problem: &str) -> Input {
                 }
             }
             ps.shuffle(&mut rng);
-            let (i, j) = ps.pop().unwrap();
-            cs[i][j] = 'A';
-            for _ in 0..2 * N {
+            for k in 0..M {
+                let (i, j) = ps.pop().unwrap();
+                cs[i][j] = (b'A' + k as u8) as char;
+            }
+            for k in 0..items {
                 let (i, j) = ps.pop().unwrap();
-                cs[i][j] = 'a';
+                cs[i][j] = (b'a' + (k % M) as u8) as char;
             }
-            for _ in 0..2 * N {
+            for _ in 0..rocks {
                 let (i, j) = ps.pop().unwrap();
                 cs[i][j] = '@';
             }
-            Input { N, M, cs }
+            Ok(Input { N, M, cs })
         }
         "B" => {
-            let N = 20;
-            let M = 3;
-            loop {
+            // 岩が多すぎると全ての荷物が穴から到達可能な盤面が作れないことがあるので、試行回数に上限を設ける
+            for _ in 0..1000 {
                 let mut cs = mat!['.'; N; N];
                 let mut ps = vec![];
                 for i in 0..N {
@@ -211,58 +474,30 @@ pub fn generate(seed: u64, problem: &str) -> Input {
                     }
                 }
                 ps.shuffle(&mut rng);
-                let mut ss = vec![];
-                for k in 0..3 {
+                for k in 0..M {
                     let (i, j) = ps.pop().unwrap();
                     cs[i][j] = (b'A' + k as u8) as char;
-                    ss.push((i, j));
                 }
-                for k in 0..3 {
-                    for _ in 0..N {
+                for k in 0..M {
+                    for _ in 0..items / M + usize::from(k < items % M) {
                         let (i, j) = ps.pop().unwrap();
                         cs[i][j] = (b'a' + k as u8) as char;
                     }
                 }
-                let mut ok = true;
-                for k in 0..3 {
-                    let t = (b'a' + k as u8) as char;
-                    let s = ss[k];
-                    let mut visited = mat![false; N; N];
-                    let mut stack = vec![s];
-                    visited[s.0][s.1] = true;
-                    while let Some((i, j)) = stack.pop() {
-                        for d in 0..4 {
-                            let (di, dj) = DIJ[d];
-                            let i = i + di;
-                            let j = j + dj;
-                            if i < N
-                                && j < N
-                                && !visited[i][j]
-                                && (cs[i][j] == '.' || cs[i][j] == t)
-                            {
-                                visited[i][j] = true;
-                                stack.push((i, j));
-                            }
-                        }
-                    }
-                    for i in 0..N {
-                        for j in 0..N {
-                            if cs[i][j] == t && !visited[i][j] {
-                                ok = false;
-                            }
-                        }
-                    }
+                for _ in 0..rocks {
+                    let (i, j) = ps.pop().unwrap();
+                    cs[i][j] = '@';
                 }
-                if ok {
-                    return Input { N, M, cs };
+                let input = Input { N, M, cs };
+                if solvability::analyze(&input).unreachable_items.is_empty() {
+                    return Ok(input);
                 }
             }
+            Err("Failed to generate a solvable input; try fewer rocks or items".to_owned())
         }
         "C" => {
-            let N = 20;
-            let M = 1;
             let perlin = Perlin::new(rng.r#gen());
-            let D = 10.0;
+            let D = params.noise_scale.unwrap_or(10.0);
             let mut ps = vec![];
             for i in 0..N {
                 for j in 0..N {
@@ -273,112 +508,201 @@ pub fn generate(seed: u64, problem: &str) -> Input {
             }
             ps.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
             let mut cs = mat!['.'; N; N];
-            for _ in 0..N * N / 2 {
+            for _ in 0..rocks {
                 let (_, i, j) = ps.pop().unwrap();
                 cs[i][j] = '@';
             }
             ps.shuffle(&mut rng);
-            let (_, i, j) = ps.pop().unwrap();
-            cs[i][j] = 'A';
-            for _ in 0..2 * N {
+            for k in 0..M {
                 let (_, i, j) = ps.pop().unwrap();
-                cs[i][j] = 'a';
+                cs[i][j] = (b'A' + k as u8) as char;
             }
-            Input { N, M, cs }
-        }
-        _ => {
-            panic!("Unknown problem: {}", problem);
+            for k in 0..items {
+                let (_, i, j) = ps.pop().unwrap();
+                cs[i][j] = (b'a' + (k % M) as u8) as char;
+            }
+            Ok(Input { N, M, cs })
         }
+        _ => Err(format!("Unknown problem: {}", problem)),
     }
 }
 
-pub fn compute_score(input: &Input, out: &Output) -> (i64, String) {
+pub fn compute_score(input: &Input, out: &Output) -> (i64, Option<ScoreError>) {
     let (mut score, err, _) = compute_score_details(input, &out.out);
-    if err.len() > 0 {
+    if err.is_some() {
         score = 0;
     }
     (score, err)
 }
 
-pub fn compute_score_details(input: &Input, out: &[Action]) -> (i64, String, ()) {
-    let mut cs = input.cs.clone();
-    let mut pos = (0, 0);
-    let mut K = 0;
-    let mut A = 0;
-    for i in 0..input.N {
-        for j in 0..input.N {
-            if cs[i][j] == 'A' {
-                pos = (i, j);
-            } else if cs[i][j] >= 'a' && cs[i][j] <= 'z' {
-                K += 1;
-            }
+/// スコア・エラー・スコアの内訳を計算する
+///
+/// 不正な操作があった場合、内訳はその直前までの操作についてのもの
+pub fn compute_score_details(
+    input: &Input,
+    out: &[Action],
+) -> (i64, Option<ScoreError>, Breakdown) {
+    let mut breakdown = Breakdown::new(input);
+    let (sim, err) = simulate(input, out, |step| breakdown.add(step));
+    match err {
+        Some(err) => {
+            breakdown.full_clear = false;
+            breakdown.prefix_score = Some(sim.score());
+            (0, Some(err), breakdown)
         }
+        None => (sim.score(), None, breakdown),
     }
-    for t in 0..out.len() {
-        match out[t] {
-            Action::Move(d) => {
-                let (di, dj) = DIJ[d];
-                pos.0 += di;
-                pos.1 += dj;
-                if pos.0 >= input.N || pos.1 >= input.N {
-                    return (0, format!("Out of the board (turn {t})"), ());
-                }
-            }
-            Action::Carry(d) => {
-                let (di, dj) = DIJ[d];
-                if (cs[pos.0][pos.1] < 'a' || cs[pos.0][pos.1] > 'z') && cs[pos.0][pos.1] != '@' {
-                    return (0, format!("No item to carry (turn {t})"), ());
-                }
-                let c = cs[pos.0][pos.1];
-                cs[pos.0][pos.1] = '.';
-                pos.0 += di;
-                pos.1 += dj;
-                if pos.0 >= input.N || pos.1 >= input.N {
-                    return (0, format!("Out of the board (turn {t})"), ());
-                }
-                if matches!(cs[pos.0][pos.1], '@' | 'a'..='z') {
-                    return (0, format!("Collision (turn {t})"), ());
-                } else if matches!(cs[pos.0][pos.1], 'A'..='Z') {
-                    if cs[pos.0][pos.1].to_ascii_lowercase() == c {
-                        A += 1;
-                    }
-                } else {
-                    assert_eq!(cs[pos.0][pos.1], '.');
-                    cs[pos.0][pos.1] = c;
-                }
-            }
-            Action::Roll(d) => {
-                let (di, dj) = DIJ[d];
-                if (cs[pos.0][pos.1] < 'a' || cs[pos.0][pos.1] > 'z') && cs[pos.0][pos.1] != '@' {
-                    return (0, format!("No item to roll (turn {t})"), ());
-                }
-                let c = cs[pos.0][pos.1];
-                cs[pos.0][pos.1] = '.';
-                let mut crt = pos;
-                loop {
-                    let next = (crt.0 + di, crt.1 + dj);
-                    if next.0 >= input.N
-                        || next.1 >= input.N
-                        || matches!(cs[next.0][next.1], '@' | 'a'..='z')
-                    {
-                        cs[crt.0][crt.1] = c;
-                        break;
-                    } else if matches!(cs[next.0][next.1], 'A'..='Z') {
-                        if cs[next.0][next.1].to_ascii_lowercase() == c {
-                            A += 1;
-                        }
-                        break;
-                    } else {
-                        crt = next;
-                    }
-                }
-            }
+}
+
+/// compute_score_details と同じシミュレーションで、各ターンに起きた出来事を列挙する
+///
+/// 不正な操作があった場合は、その直前までの出来事とエラーを返す
+pub fn compute_events(input: &Input, out: &[Action]) -> (Vec<Event>, Option<ScoreError>) {
+    let mut events = vec![];
+    let (_, err) = simulate(input, out, |step| events.extend(Event::from_step(step)));
+    (events, err)
+}
+
+/// out を先頭から実行し、各操作の結果を f に渡す
+fn simulate(
+    input: &Input,
+    out: &[Action],
+    mut f: impl FnMut(&StepResult),
+) -> (Sim, Option<ScoreError>) {
+    let mut sim = Sim::new(input);
+    for &a in out {
+        match sim.step(a) {
+            Ok(step) => f(&step),
+            Err(err) => return (sim, Some(err)),
         }
     }
-    let score = if A == K {
-        (1e6 * (1.0 + (1e4 / out.len() as f64).log2())).round() as i64
-    } else {
-        (1e6 * A as f64 / K as f64).round() as i64
-    };
-    (score, String::new(), ())
+    (sim, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// パースエラーの (行番号, 列番号)
+    fn input_err(f: &str) -> (usize, Option<usize>) {
+        let err = parse_input(f).unwrap_err();
+        (err.line, err.col)
+    }
+
+    #[test]
+    fn parse_input_accepts_valid_input() {
+        let input = parse_input("3 2\nA.b\n.@.\n..B\n").unwrap();
+        assert_eq!((input.N, input.M), (3, 2));
+        assert_eq!(input.cs[1], vec!['.', '@', '.']);
+    }
+
+    #[test]
+    fn parse_input_bad_header() {
+        assert_eq!(input_err(""), (1, None));
+        assert_eq!(input_err("3\nA..\n...\n...\n"), (1, None));
+        assert_eq!(input_err("0 1\n"), (1, Some(1)));
+        assert_eq!(input_err("3 x\nA..\n...\n...\n"), (1, Some(3)));
+        assert_eq!(input_err("3 27\nA..\n...\n...\n"), (1, Some(3)));
+        assert_eq!(input_err("3 1 0\nA..\n...\n...\n"), (1, Some(5)));
+    }
+
+    #[test]
+    fn parse_input_bad_rows() {
+        // 短い行
+        assert_eq!(input_err("3 1\nA..\n..\n...\n"), (3, None));
+        // 長い行
+        assert_eq!(input_err("3 1\nA..\n....\n...\n"), (3, None));
+        // 行が足りない
+        assert_eq!(input_err("3 1\nA..\n"), (3, None));
+        // 使えない文字
+        assert_eq!(input_err("3 1\nA.#\n...\n...\n"), (2, Some(3)));
+    }
+
+    #[test]
+    fn parse_input_start_cell() {
+        assert_eq!(input_err("3 1\nA..\n..A\n...\n"), (3, Some(3)));
+        assert_eq!(input_err("3 1\nB..\n...\n...\n"), (2, None));
+    }
+
+    #[test]
+    fn parse_input_holes_and_items() {
+        // 穴の色は A の1色なのに M = 2
+        assert_eq!(input_err("3 2\nA..\n...\n...\n"), (1, Some(3)));
+        // 穴 B のない荷物 b
+        assert_eq!(input_err("3 1\nA..\n.b.\n...\n"), (3, Some(2)));
+    }
+
+    #[test]
+    fn parse_input_trailing_content() {
+        assert!(parse_input("3 1\nA..\n...\n...\n\n\n").is_ok());
+        assert_eq!(input_err("3 1\nA..\n...\n...\n\nfoo\n"), (6, Some(1)));
+    }
+
+    fn output(f: &str) -> Output {
+        let input = parse_input("3 1\nA..\n...\n...\n").unwrap();
+        parse_output(&input, f).unwrap()
+    }
+
+    /// パースエラーの (ターン, 行番号, トークン)
+    fn output_err(f: &str) -> (usize, Option<usize>, Option<String>) {
+        let input = parse_input("3 1\nA..\n...\n...\n").unwrap();
+        let err = parse_output(&input, f).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::ParseError);
+        (err.turn, err.line, err.token)
+    }
+
+    #[test]
+    fn parse_output_reads_actions_across_lines() {
+        let out = output("1\nU\n").out;
+        assert!(matches!(out[..], [Action::Move(0)]));
+        let out = output("1 D 2\nR\n3 L\n").out;
+        assert!(matches!(
+            out[..],
+            [Action::Move(1), Action::Carry(3), Action::Roll(2)]
+        ));
+    }
+
+    #[test]
+    fn parse_output_count_line() {
+        assert_eq!(output("2\n1 D\n1 R\n").out.len(), 2);
+        assert_eq!(output("# c\n\n1\n1 D\n").out.len(), 1);
+        assert_eq!(output("0\n").out.len(), 0);
+        assert_eq!(
+            output_err("3\n1 D\n1 R\n"),
+            (0, Some(1), Some("3".to_owned()))
+        );
+    }
+
+    #[test]
+    fn parse_output_comments() {
+        let out = output("#c first\n1 D\n# ignored\n#c second\n1 R\n#c tail\n");
+        assert_eq!(out.annotations[0].comments, vec!["first"]);
+        assert_eq!(out.annotations[1].comments, vec!["second", "tail"]);
+        // 種類と方向の間のコメントは、その操作の注釈になる
+        let out = output("1\n#c mid\nD\n");
+        assert_eq!(out.annotations[0].comments, vec!["mid"]);
+    }
+
+    #[test]
+    fn parse_output_ignores_invalid_highlights() {
+        let out = output("#h 0 1 red\n#h 3 0 red\n#h 0 0\n#h 0 0 \"x\"\n1 D\n");
+        assert_eq!(out.out.len(), 1);
+        assert_eq!(
+            out.annotations[0].highlights,
+            vec![((0, 1), "red".to_owned())]
+        );
+        assert_eq!(out.warnings.len(), 3);
+        assert!(out.warnings[0].starts_with("line 2:"));
+    }
+
+    #[test]
+    fn parse_output_error_positions() {
+        assert_eq!(output_err("1 D\n4 R\n"), (1, Some(2), Some("4".to_owned())));
+        assert_eq!(output_err("1 D\n1 X\n"), (1, Some(2), Some("X".to_owned())));
+        assert_eq!(output_err("1 D\n1 r\n"), (1, Some(2), Some("r".to_owned())));
+        // 方向が足りない場合は、種類を書いた行を指す
+        assert_eq!(output_err("1 D 2\n\n"), (1, Some(1), None));
+        // 1行目が2トークンなので、奇数個でも操作の数の行とはみなさない
+        assert_eq!(output_err("1 D\nx\n"), (1, Some(2), Some("x".to_owned())));
+    }
 }
@@ -0,0 +1,74 @@
+/// 入力生成のパラメータ
+///
+/// None の項目は問題ごとの既定値を使う。全て None なら従来と同じ入力が生成される
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GenParams {
+    /// 盤面の大きさ (既定値 20)
+    pub N: Option<usize>,
+    /// 色の数 (既定値は A・C が 1、B が 3)
+    pub M: Option<usize>,
+    /// 荷物の総数 (既定値は A・C が 2N、B が 1色あたり N 個)
+    pub items: Option<usize>,
+    /// 岩の数 (既定値は A が 2N、B が 0、C が rock_density から計算した値)
+    pub rocks: Option<usize>,
+    /// 岩の数をマスの数に対する割合で指定する (rocks が指定されていればそちらを優先、C の既定値は 0.5)
+    pub rock_density: Option<f64>,
+    /// C で岩の配置に使うパーリンノイズのスケール (既定値 10.0、大きいほど岩の塊が大きくなる)
+    pub noise_scale: Option<f64>,
+}
+
+impl GenParams {
+    /// 値の範囲を確かめる (N は parse_input と同じ 1..=1000、M は 1..=26)
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(N) = self.N.filter(|N| !(1..=1000).contains(N)) {
+            return Err(format!("N must be in 1..=1000: {}", N));
+        }
+        if let Some(M) = self.M.filter(|M| !(1..=26).contains(M)) {
+            return Err(format!("M must be in 1..=26: {}", M));
+        }
+        if let Some(d) = self.rock_density.filter(|d| !(0.0..=1.0).contains(d)) {
+            return Err(format!("rock_density must be in [0, 1]: {}", d));
+        }
+        if let Some(s) = self.noise_scale.filter(|s| !(s.is_finite() && *s > 0.0)) {
+            return Err(format!("noise_scale must be a positive number: {}", s));
+        }
+        Ok(())
+    }
+
+    /// 岩の数 (rocks、rock_density、既定値の順に使う)
+    pub fn rocks_or(&self, N: usize, default: usize) -> usize {
+        self.rocks
+            .or(self
+                .rock_density
+                .map(|d| (d * (N * N) as f64).round() as usize))
+            .unwrap_or(default)
+    }
+}
+
+/// `N=30 items=80 noise_scale=5` のような `key=value` の列 (空白またはカンマ区切り) から読む
+impl std::str::FromStr for GenParams {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<GenParams, String> {
+        let mut params = GenParams::default();
+        for kv in s.split(|c: char| c.is_whitespace() || c == ',') {
+            if kv.is_empty() {
+                continue;
+            }
+            let Some((key, value)) = kv.split_once('=') else {
+                return Err(format!("Expected key=value: {}", kv));
+            };
+            let invalid = || format!("Invalid value for {}: {}", key, value);
+            match key {
+                "N" => params.N = Some(value.parse().map_err(|_| invalid())?),
+                "M" => params.M = Some(value.parse().map_err(|_| invalid())?),
+                "items" => params.items = Some(value.parse().map_err(|_| invalid())?),
+                "rocks" => params.rocks = Some(value.parse().map_err(|_| invalid())?),
+                "rock_density" => params.rock_density = Some(value.parse().map_err(|_| invalid())?),
+                "noise_scale" => params.noise_scale = Some(value.parse().map_err(|_| invalid())?),
+                _ => return Err(format!("Unknown parameter: {}", key)),
+            }
+        }
+        Ok(params)
+    }
+}
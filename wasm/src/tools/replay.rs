@@ -0,0 +1,145 @@
+use super::{
+    Action, Annotation, Breakdown, Input, Output, ScoreError, Sim, StepResult, MAX_ACTIONS,
+};
+
+/// チェックポイントを保存する間隔 (ターン数)
+const INTERVAL: usize = 100;
+
+/// 操作列を一度だけシミュレートし、一定間隔で盤面を保存しておく
+///
+/// 任意のターンの盤面は直前のチェックポイントから高々 INTERVAL 手の再生で求まる
+///
+/// 操作の数が MAX_ACTIONS を超える場合も、不正な操作が現れるまでは最後まで再生する
+/// (スコアは 0 で、エラーは MAX_ACTIONS 手目の TooManyActions になり、上限より後の不正な操作は replay_err に入る)
+#[derive(Clone, Debug)]
+pub struct Replay {
+    pub actions: Vec<Action>,
+    /// annotations[t] は t 番目の操作に付けられた注釈
+    pub annotations: Vec<Annotation>,
+    /// checkpoints[k] は k * INTERVAL ターン後の盤面
+    checkpoints: Vec<Sim>,
+    /// steps[t] は t 番目の操作の結果 (正常に実行できた操作のみ)
+    pub steps: Vec<StepResult>,
+    /// 正常に実行できた操作の数
+    pub valid_turns: usize,
+    /// 操作列全体のスコア
+    pub score: i64,
+    /// 最初の不正な操作のエラー
+    pub err: Option<ScoreError>,
+    /// 操作の数が上限を超えた後に現れた不正な操作のエラー (再生はそこで止まる)
+    pub replay_err: Option<ScoreError>,
+    /// スコアの内訳 (不正な操作がある場合・上限を超えた場合はその直前まで)
+    pub breakdown: Breakdown,
+    /// 出力中の読み飛ばした注釈についての警告
+    pub warnings: Vec<String>,
+}
+
+impl Replay {
+    pub fn new(input: &Input, out: Output) -> Replay {
+        let Output {
+            out: actions,
+            annotations,
+            warnings,
+        } = out;
+        let mut sim = Sim::new(input);
+        let mut checkpoints = vec![sim.clone()];
+        let mut err = None;
+        let mut replay_err = None;
+        let mut breakdown = Breakdown::new(input);
+        let mut steps = vec![];
+        for &a in &actions {
+            if sim.turn == MAX_ACTIONS {
+                err = Some(ScoreError::too_many_actions(MAX_ACTIONS));
+                breakdown.prefix_score = Some(sim.score());
+            }
+            match sim.step(a) {
+                Ok(step) => {
+                    if err.is_none() {
+                        breakdown.add(&step);
+                    }
+                    steps.push(step);
+                }
+                Err(e) => {
+                    if err.is_none() {
+                        breakdown.prefix_score = Some(sim.score());
+                        err = Some(e);
+                    } else {
+                        replay_err = Some(e);
+                    }
+                    break;
+                }
+            }
+            if sim.turn.is_multiple_of(INTERVAL) {
+                checkpoints.push(sim.clone());
+            }
+        }
+        if err.is_some() {
+            breakdown.full_clear = false;
+        }
+        let score = if err.is_none() { sim.score() } else { 0 };
+        Replay {
+            valid_turns: sim.turn,
+            actions,
+            annotations,
+            checkpoints,
+            steps,
+            score,
+            err,
+            replay_err,
+            breakdown,
+            warnings,
+        }
+    }
+
+    pub fn max_turn(&self) -> usize {
+        self.actions.len()
+    }
+
+    /// turn ターン目の盤面で強調するエラー (そのターンより前に起きたもの、上限より後の不正な操作を優先)
+    pub fn error_at(&self, turn: usize) -> Option<&ScoreError> {
+        self.replay_err
+            .iter()
+            .chain(&self.err)
+            .find(|e| turn > e.turn)
+    }
+
+    /// turn ターン目の盤面に表示する注釈 (turn 番目の操作、つまり直前に実行した操作に付けられたもの)
+    pub fn annotation(&self, turn: usize) -> Option<&Annotation> {
+        turn.checked_sub(1).and_then(|t| self.annotations.get(t))
+    }
+
+    /// turn 手目までに実行した操作の結果
+    pub fn steps(&self, turn: usize) -> &[StepResult] {
+        &self.steps[..turn.min(self.steps.len())]
+    }
+
+    /// turn 手目までの操作で、プレイヤーが各マスにいたターン数 (最初の位置を含む)
+    ///
+    /// ヒートマップの描画に使う
+    pub fn visits(&self, turn: usize) -> Vec<Vec<usize>> {
+        let start = &self.checkpoints[0];
+        let mut visits = vec![vec![0; start.N]; start.N];
+        visits[start.pos.0][start.pos.1] += 1;
+        for step in self.steps(turn) {
+            visits[step.to.0][step.to.1] += 1;
+        }
+        visits
+    }
+
+    /// turn 手目の操作 (turn ターン目の盤面の直前に実行した操作) の結果
+    pub fn last_step(&self, turn: usize) -> Option<&StepResult> {
+        self.steps(turn).last()
+    }
+
+    /// turn 手目までの操作を実行した後の盤面
+    ///
+    /// 不正な操作がある場合は、その直前の盤面で止まる
+    pub fn state(&self, turn: usize) -> Sim {
+        let turn = turn.min(self.valid_turns);
+        let mut sim = self.checkpoints[turn / INTERVAL].clone();
+        for &a in &self.actions[sim.turn..turn] {
+            sim.step(a).unwrap();
+        }
+        sim
+    }
+}
@@ -1,13 +1,21 @@
 #![allow(non_snake_case)]
 
-use super::{compute_score, parse_input, parse_output};
+use super::{compute_score, parse_input, parse_output, ScoreError};
 
-pub fn score(input: String, output: String) -> (i64, String) {
-    let input = parse_input(&input);
+pub fn score(input: String, output: String) -> (i64, Option<ScoreError>) {
+    let input = match parse_input(&input) {
+        Ok(input) => input,
+        Err(err) => {
+            return (
+                0,
+                Some(ScoreError::parse(0, format!("Invalid input: {}", err))),
+            )
+        }
+    };
     let out = parse_output(&input, &output);
     let (score, err) = match out {
         Ok(out) => compute_score(&input, &out),
-        Err(err) => (0, err),
+        Err(err) => (0, Some(err)),
     };
     (score, err)
-}
\ No newline at end of file
+}
@@ -0,0 +1,331 @@
+#![allow(non_snake_case)]
+
+use super::{Action, ErrorKind, Input, ScoreError, DIJ};
+use std::collections::BTreeMap;
+
+/// 盤面の状態
+///
+/// スコア計算とビジュアライザの描画はどちらもこの型の `step` で盤面を進める
+#[derive(Clone, Debug)]
+pub struct Sim {
+    pub N: usize,
+    pub cs: Vec<Vec<char>>,
+    /// プレイヤーの位置
+    pub pos: (usize, usize),
+    /// 荷物の総数
+    pub K: usize,
+    /// 正しい穴に運ばれた荷物の数
+    pub A: usize,
+    /// 実行済みの操作の数
+    pub turn: usize,
+    /// 穴のマスごとの、同じ色の荷物が落ちた回数 (一度も落ちていない穴は含まない)
+    pub deliveries: BTreeMap<(usize, usize), usize>,
+}
+
+/// 1回の操作の結果
+#[derive(Clone, Copy, Debug)]
+pub struct StepResult {
+    /// 操作の番号 (0-indexed)
+    pub turn: usize,
+    pub action: Action,
+    /// 操作前のプレイヤーの位置
+    pub from: (usize, usize),
+    /// 操作後のプレイヤーの位置
+    pub to: (usize, usize),
+    /// 動かした荷物または岩 (Move のときは None)
+    pub item: Option<char>,
+    /// 荷物が止まったマス、または荷物が落ちた穴のマス
+    pub item_to: (usize, usize),
+    /// 荷物が落ちた穴
+    pub hole: Option<char>,
+}
+
+impl StepResult {
+    /// 荷物が同じ色の穴に落ちたか
+    pub fn delivered(&self) -> bool {
+        matches!((self.item, self.hole), (Some(c), Some(h)) if h.to_ascii_lowercase() == c)
+    }
+}
+
+impl Sim {
+    pub fn new(input: &Input) -> Sim {
+        let mut pos = (0, 0);
+        let mut K = 0;
+        for i in 0..input.N {
+            for j in 0..input.N {
+                if input.cs[i][j] == 'A' {
+                    pos = (i, j);
+                } else if input.cs[i][j].is_ascii_lowercase() {
+                    K += 1;
+                }
+            }
+        }
+        Sim {
+            N: input.N,
+            cs: input.cs.clone(),
+            pos,
+            K,
+            A: 0,
+            turn: 0,
+            deliveries: BTreeMap::new(),
+        }
+    }
+
+    fn is_inside(&self, p: (usize, usize)) -> bool {
+        p.0 < self.N && p.1 < self.N
+    }
+
+    fn target(&self, p: (usize, usize)) -> Option<(usize, usize)> {
+        if self.is_inside(p) {
+            Some(p)
+        } else {
+            None
+        }
+    }
+
+    /// 操作を1つ実行する
+    ///
+    /// 不正な操作の場合は盤面を変更せずにエラーを返す
+    #[allow(clippy::result_large_err)]
+    pub fn step(&mut self, action: Action) -> Result<StepResult, ScoreError> {
+        let t = self.turn;
+        let from = self.pos;
+        let mut ret = StepResult {
+            turn: t,
+            action,
+            from,
+            to: from,
+            item: None,
+            item_to: from,
+            hole: None,
+        };
+        match action {
+            Action::Move(d) => {
+                let (di, dj) = DIJ[d];
+                let to = (from.0.wrapping_add(di), from.1.wrapping_add(dj));
+                if !self.is_inside(to) {
+                    return Err(ScoreError::new(ErrorKind::OutOfBoard, t, from, None));
+                }
+                ret.to = to;
+            }
+            Action::Carry(d) => {
+                let (di, dj) = DIJ[d];
+                let c = self.cs[from.0][from.1];
+                let to = (from.0.wrapping_add(di), from.1.wrapping_add(dj));
+                if !c.is_ascii_lowercase() && c != '@' {
+                    let target = self.target(to);
+                    return Err(ScoreError::new(ErrorKind::NoItemToCarry, t, from, target));
+                }
+                if !self.is_inside(to) {
+                    return Err(ScoreError::new(ErrorKind::OutOfBoard, t, from, None));
+                }
+                let target = self.cs[to.0][to.1];
+                if matches!(target, '@' | 'a'..='z') {
+                    return Err(ScoreError::new(ErrorKind::Collision, t, from, Some(to)));
+                }
+                self.cs[from.0][from.1] = '.';
+                if target.is_ascii_uppercase() {
+                    ret.hole = Some(target);
+                } else {
+                    self.cs[to.0][to.1] = c;
+                }
+                ret.to = to;
+                ret.item = Some(c);
+                ret.item_to = to;
+            }
+            Action::Roll(d) => {
+                let (di, dj) = DIJ[d];
+                let c = self.cs[from.0][from.1];
+                if !c.is_ascii_lowercase() && c != '@' {
+                    let to = (from.0.wrapping_add(di), from.1.wrapping_add(dj));
+                    let target = self.target(to);
+                    return Err(ScoreError::new(ErrorKind::NoItemToRoll, t, from, target));
+                }
+                self.cs[from.0][from.1] = '.';
+                let mut crt = from;
+                loop {
+                    let next = (crt.0.wrapping_add(di), crt.1.wrapping_add(dj));
+                    if !self.is_inside(next) || matches!(self.cs[next.0][next.1], '@' | 'a'..='z') {
+                        self.cs[crt.0][crt.1] = c;
+                        break;
+                    } else if self.cs[next.0][next.1].is_ascii_uppercase() {
+                        ret.hole = Some(self.cs[next.0][next.1]);
+                        crt = next;
+                        break;
+                    } else {
+                        crt = next;
+                    }
+                }
+                ret.item = Some(c);
+                ret.item_to = crt;
+            }
+        }
+        if ret.delivered() {
+            self.A += 1;
+            *self.deliveries.entry(ret.item_to).or_default() += 1;
+        }
+        self.pos = ret.to;
+        self.turn += 1;
+        Ok(ret)
+    }
+
+    /// 現在までの操作列に対するスコア
+    pub fn score(&self) -> i64 {
+        if self.A == self.K {
+            (1e6 * (1.0 + (1e4 / self.turn as f64).log2())).round() as i64
+        } else {
+            (1e6 * self.A as f64 / self.K as f64).round() as i64
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const U: usize = 0;
+    const D: usize = 1;
+    const L: usize = 2;
+    const R: usize = 3;
+
+    fn sim(rows: &[&str]) -> Sim {
+        let cs = rows
+            .iter()
+            .map(|r| r.chars().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        Sim::new(&Input {
+            N: cs.len(),
+            M: 0,
+            cs,
+        })
+    }
+
+    /// 不正な操作のあとで盤面が変わっていないことを確かめる
+    fn step_err(sim: &mut Sim, action: Action) -> ScoreError {
+        let (cs, pos, A, turn) = (sim.cs.clone(), sim.pos, sim.A, sim.turn);
+        let err = sim.step(action).unwrap_err();
+        assert_eq!(sim.cs, cs);
+        assert_eq!(sim.pos, pos);
+        assert_eq!(sim.A, A);
+        assert_eq!(sim.turn, turn);
+        err
+    }
+
+    #[test]
+    fn carry_into_matching_hole() {
+        let mut sim = sim(&["AbB", "...", "..."]);
+        sim.step(Action::Move(R)).unwrap();
+        let ret = sim.step(Action::Carry(R)).unwrap();
+        assert_eq!(ret.item, Some('b'));
+        assert_eq!(ret.hole, Some('B'));
+        assert!(ret.delivered());
+        assert_eq!(sim.cs[0], vec!['A', '.', 'B']);
+        assert_eq!(sim.pos, (0, 2));
+        assert_eq!((sim.A, sim.K, sim.turn), (1, 1, 2));
+        assert_eq!(sim.deliveries.get(&(0, 2)), Some(&1));
+    }
+
+    #[test]
+    fn carry_into_other_hole() {
+        let mut sim = sim(&["AbC", "...", "..."]);
+        sim.step(Action::Move(R)).unwrap();
+        let ret = sim.step(Action::Carry(R)).unwrap();
+        assert_eq!(ret.hole, Some('C'));
+        assert!(!ret.delivered());
+        assert_eq!(sim.cs[0], vec!['A', '.', 'C']);
+        assert_eq!(sim.A, 0);
+        assert!(sim.deliveries.is_empty());
+    }
+
+    #[test]
+    fn roll_into_matching_hole() {
+        let mut sim = sim(&["Ab.B", "....", "....", "...."]);
+        sim.step(Action::Move(R)).unwrap();
+        let ret = sim.step(Action::Roll(R)).unwrap();
+        assert_eq!(ret.item_to, (0, 3));
+        assert_eq!(ret.hole, Some('B'));
+        assert!(ret.delivered());
+        assert_eq!(sim.cs[0], vec!['A', '.', '.', 'B']);
+        // Roll ではプレイヤーは動かない
+        assert_eq!(sim.pos, (0, 1));
+        assert_eq!(sim.A, 1);
+    }
+
+    #[test]
+    fn roll_into_other_hole() {
+        let mut sim = sim(&["Ab.C", "....", "....", "...."]);
+        sim.step(Action::Move(R)).unwrap();
+        let ret = sim.step(Action::Roll(R)).unwrap();
+        assert_eq!(ret.hole, Some('C'));
+        assert!(!ret.delivered());
+        assert_eq!(sim.cs[0], vec!['A', '.', '.', 'C']);
+        assert_eq!(sim.A, 0);
+    }
+
+    #[test]
+    fn roll_stops_before_obstacle_and_edge() {
+        let mut sim = sim(&["Ab.@", "c...", "....", "...."]);
+        sim.step(Action::Move(R)).unwrap();
+        let ret = sim.step(Action::Roll(R)).unwrap();
+        assert_eq!((ret.item_to, ret.hole), ((0, 2), None));
+        assert_eq!(sim.cs[0], vec!['A', '.', 'b', '@']);
+        sim.step(Action::Move(L)).unwrap();
+        sim.step(Action::Move(D)).unwrap();
+        let ret = sim.step(Action::Roll(D)).unwrap();
+        assert_eq!((ret.item_to, ret.hole), ((3, 0), None));
+        assert_eq!(sim.cs[3][0], 'c');
+    }
+
+    #[test]
+    fn carry_and_roll_rock() {
+        let mut sim = sim(&["A@..", "....", "....", "...."]);
+        sim.step(Action::Move(R)).unwrap();
+        let ret = sim.step(Action::Carry(R)).unwrap();
+        assert_eq!(ret.item, Some('@'));
+        assert_eq!(sim.cs[0], vec!['A', '.', '@', '.']);
+        let ret = sim.step(Action::Roll(R)).unwrap();
+        assert_eq!(ret.item_to, (0, 3));
+        assert_eq!(sim.cs[0], vec!['A', '.', '.', '@']);
+        assert_eq!(sim.A, 0);
+    }
+
+    #[test]
+    fn rock_falls_into_hole() {
+        let mut sim = sim(&["A@.B", "....", "....", "...."]);
+        sim.step(Action::Move(R)).unwrap();
+        let ret = sim.step(Action::Roll(R)).unwrap();
+        assert_eq!(ret.hole, Some('B'));
+        assert!(!ret.delivered());
+        assert_eq!(sim.cs[0], vec!['A', '.', '.', 'B']);
+        assert_eq!(sim.A, 0);
+    }
+
+    #[test]
+    fn no_item_is_reported_before_out_of_board() {
+        let mut sim = sim(&["Ab.", "...", "..."]);
+        let err = step_err(&mut sim, Action::Carry(U));
+        assert_eq!(err.kind, ErrorKind::NoItemToCarry);
+        assert_eq!((err.pos, err.target), (Some((0, 0)), None));
+        let err = step_err(&mut sim, Action::Roll(U));
+        assert_eq!(err.kind, ErrorKind::NoItemToRoll);
+        sim.step(Action::Move(R)).unwrap();
+        let err = step_err(&mut sim, Action::Carry(U));
+        assert_eq!(err.kind, ErrorKind::OutOfBoard);
+        assert_eq!(err.turn, 1);
+    }
+
+    #[test]
+    fn errors_leave_board_unchanged() {
+        let mut sim = sim(&["Abc", "@..", "..."]);
+        let err = step_err(&mut sim, Action::Move(U));
+        assert_eq!(err.kind, ErrorKind::OutOfBoard);
+        sim.step(Action::Move(R)).unwrap();
+        let err = step_err(&mut sim, Action::Carry(R));
+        assert_eq!(err.kind, ErrorKind::Collision);
+        assert_eq!(err.target, Some((0, 2)));
+        sim.step(Action::Move(D)).unwrap();
+        let err = step_err(&mut sim, Action::Roll(R));
+        assert_eq!(err.kind, ErrorKind::NoItemToRoll);
+        assert_eq!(err.target, Some((1, 2)));
+    }
+}
@@ -0,0 +1,129 @@
+#![allow(non_snake_case)]
+
+use super::{Input, DIJ};
+
+/// 入力の解きやすさの診断結果
+///
+/// 荷物は空きマスと同じ色の荷物のマスだけを通って運べるものとして調べる。
+/// 岩や他の色の荷物をどかせば解ける場合もあるので、ここで見つかった問題は警告として扱う
+/// (プレイヤー自身は岩や荷物の上も移動できるので、スタート地点が塞がれることはない)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Solvability {
+    /// 同じ色の穴まで運べない荷物の (位置, 色)
+    pub unreachable_items: Vec<((usize, usize), char)>,
+    /// 周囲4マスが全て塞がれていて、荷物を入れられない穴の (位置, 色)
+    pub isolated_holes: Vec<((usize, usize), char)>,
+}
+
+impl Solvability {
+    pub fn is_ok(&self) -> bool {
+        *self == Solvability::default()
+    }
+
+    /// 見つかった問題を1件1行で表したもの
+    pub fn warnings(&self) -> Vec<String> {
+        let mut ret = vec![];
+        for &((i, j), c) in &self.isolated_holes {
+            ret.push(format!(
+                "hole {} at ({}, {}) is surrounded by obstacles",
+                c, i, j
+            ));
+        }
+        for &((i, j), c) in &self.unreachable_items {
+            ret.push(format!(
+                "item {} at ({}, {}) cannot reach hole {}",
+                c,
+                i,
+                j,
+                c.to_ascii_uppercase()
+            ));
+        }
+        ret
+    }
+}
+
+/// 色 c の荷物が通れるマスか
+fn passable(c: char, t: char) -> bool {
+    t == '.' || t == c
+}
+
+pub fn analyze(input: &Input) -> Solvability {
+    let N = input.N;
+    let cs = &input.cs;
+    let mut ret = Solvability::default();
+    let neighbors = |i: usize, j: usize| {
+        DIJ.iter().filter_map(move |&(di, dj)| {
+            let i = i.wrapping_add(di);
+            let j = j.wrapping_add(dj);
+            if i < N && j < N {
+                Some((i, j))
+            } else {
+                None
+            }
+        })
+    };
+    // 色ごとに、同じ色の穴から荷物が通れるマスを辿る (訪問済みの表は盤面にある穴の色の数だけ確保する)
+    let mut slot = [None; 26];
+    let mut visited = vec![];
+    for &h in cs.iter().flatten() {
+        if h.is_ascii_uppercase() && slot[(h as u8 - b'A') as usize].is_none() {
+            slot[(h as u8 - b'A') as usize] = Some(visited.len());
+            visited.push(vec![vec![false; N]; N]);
+        }
+    }
+    for i in 0..N {
+        for j in 0..N {
+            let h = cs[i][j];
+            if !h.is_ascii_uppercase() {
+                continue;
+            }
+            let c = h.to_ascii_lowercase();
+            if neighbors(i, j).all(|(i, j)| !passable(c, cs[i][j])) {
+                ret.isolated_holes.push(((i, j), h));
+            }
+            let visited = &mut visited[slot[(h as u8 - b'A') as usize].unwrap()];
+            let mut stack = vec![(i, j)];
+            visited[i][j] = true;
+            while let Some((i, j)) = stack.pop() {
+                for (i, j) in neighbors(i, j) {
+                    if !visited[i][j] && passable(c, cs[i][j]) {
+                        visited[i][j] = true;
+                        stack.push((i, j));
+                    }
+                }
+            }
+        }
+    }
+    for i in 0..N {
+        for j in 0..N {
+            let c = cs[i][j];
+            if c.is_ascii_lowercase()
+                && !slot[(c as u8 - b'a') as usize].is_some_and(|k| visited[k][i][j])
+            {
+                ret.unreachable_items.push(((i, j), c));
+            }
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::parse_input;
+
+    #[test]
+    fn finds_isolated_holes_and_unreachable_items() {
+        let input = parse_input("5 3\nA@b..\n@....\n..@..\n.@C@.\nc.@.B\n").unwrap();
+        let ret = analyze(&input);
+        assert_eq!(ret.isolated_holes, vec![((0, 0), 'A'), ((3, 2), 'C')]);
+        assert_eq!(ret.unreachable_items, vec![((4, 0), 'c')]);
+        assert_eq!(ret.warnings().len(), 3);
+    }
+
+    #[test]
+    fn reachable_input_is_ok() {
+        let input = parse_input("3 2\nA.b\n.@.\na.B\n").unwrap();
+        assert!(analyze(&input).is_ok());
+    }
+}